@@ -0,0 +1,44 @@
+//! Custom memory allocation for OCI environments
+
+use std::sync::Arc;
+use {c_void, size_t};
+
+/// Memory allocator OCI uses for everything it allocates in an environment, set with
+/// [`EnvironmentBuilder::allocator`](struct.EnvironmentBuilder.html#method.allocator).
+///
+/// OCI does not pass the size of a block to `free`, so an allocator accounting memory has to
+/// keep it itself. The methods are called from every thread using the environment and must not
+/// panic, as a panic cannot unwind into OCI and aborts the process.
+pub trait OciAllocator: Send + Sync {
+    /// Allocates `size` bytes, returns null on failure.
+    fn alloc(&self, size: usize) -> *mut c_void;
+
+    /// Resizes the block at `ptr` to `size` bytes, returns null on failure.
+    fn realloc(&self, ptr: *mut c_void, size: usize) -> *mut c_void;
+
+    /// Frees the block at `ptr`.
+    fn free(&self, ptr: *mut c_void);
+}
+
+/// Context passed to OCI with the callbacks below, boxed so that it is a thin pointer.
+pub type Context = Box<Arc<dyn OciAllocator>>;
+
+fn allocator<'a>(ctxp: *mut c_void) -> &'a dyn OciAllocator {
+    // `ctxp` is the `Context` kept by the environment until after its handle is freed.
+    unsafe { &**(ctxp as *const Arc<dyn OciAllocator>) }
+}
+
+/// `malocfp` callback of `OCIEnvNlsCreate()`.
+pub extern "C" fn malloc(ctxp: *mut c_void, size: size_t) -> *mut c_void {
+    allocator(ctxp).alloc(size as usize)
+}
+
+/// `ralocfp` callback of `OCIEnvNlsCreate()`.
+pub extern "C" fn realloc(ctxp: *mut c_void, memptr: *mut c_void, newsize: size_t) -> *mut c_void {
+    allocator(ctxp).realloc(memptr, newsize as usize)
+}
+
+/// `mfreefp` callback of `OCIEnvNlsCreate()`.
+pub extern "C" fn free(ctxp: *mut c_void, memptr: *mut c_void) {
+    allocator(ctxp).free(memptr)
+}
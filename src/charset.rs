@@ -0,0 +1,178 @@
+//! Client and national character sets
+
+use std::str;
+use OracleError;
+
+/// `AL32UTF8`
+pub const AL32UTF8: u16 = 873;
+
+/// `UTF8`
+const UTF8: u16 = 871;
+
+/// `WE8ISO8859P1`
+const WE8ISO8859P1: u16 = 31;
+
+/// `US7ASCII`
+const US7ASCII: u16 = 1;
+
+/// `AL16UTF16`
+const AL16UTF16: u16 = 2000;
+
+/// `OCI_UTF16ID`
+const UTF16ID: u16 = 1000;
+
+/// Character set given by its Oracle name, e.g. `AL32UTF8`, or its id.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Charset {
+    /// Id as returned by `OCINlsCharSetNameToId()`, e.g. `873` for `AL32UTF8`.
+    Id(u16),
+
+    /// Oracle name, resolved with [`oci_nls_charset_name_to_id`](fn.oci_nls_charset_name_to_id.html).
+    Name(String),
+}
+
+impl From<u16> for Charset {
+    fn from(id: u16) -> Charset {
+        Charset::Id(id)
+    }
+}
+
+impl<'a> From<&'a str> for Charset {
+    fn from(name: &'a str) -> Charset {
+        Charset::Name(name.to_string())
+    }
+}
+
+impl From<String> for Charset {
+    fn from(name: String) -> Charset {
+        Charset::Name(name)
+    }
+}
+
+/// How text is converted between Rust strings and the client character set.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Encoding {
+    /// `AL32UTF8` and `UTF8`, text is passed unchanged.
+    Utf8,
+
+    /// `WE8ISO8859P1`, every character is a single byte with the value of its code point.
+    Latin1,
+
+    /// `US7ASCII`
+    Ascii,
+}
+
+impl Encoding {
+    /// Encoding of the client character set `id`, character sets with no encoding are rejected.
+    pub fn of(id: u16) -> Result<Encoding, OracleError> {
+        match id {
+            AL32UTF8 | UTF8     => Ok(Encoding::Utf8),
+            WE8ISO8859P1        => Ok(Encoding::Latin1),
+            US7ASCII            => Ok(Encoding::Ascii),
+            AL16UTF16 | UTF16ID => Err(OracleError::new("UTF-16 is only supported as the national character set",
+                                                        "Encoding::of")),
+            _ => Err(OracleError::new(&format!("Character set {} is not supported as the client character set", id),
+                                      "Encoding::of")),
+        }
+    }
+
+    /// Converts `text` into the character set.
+    pub fn encode(&self, text: &str) -> Result<Vec<u8>, OracleError> {
+        let limit = match *self {
+            Encoding::Utf8   => return Ok(text.as_bytes().to_vec()),
+            Encoding::Latin1 => 0xFF,
+            Encoding::Ascii  => 0x7F,
+        };
+        text.chars()
+            .map(|c| if c as u32 <= limit { Ok(c as u8) } else { Err(c) })
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|c| OracleError::new(&format!("{:?} is not representable in {:?}", c, self),
+                                          "Encoding::encode"))
+    }
+
+    /// Converts `bytes` in the character set into a string.
+    pub fn decode(&self, bytes: &[u8]) -> Result<String, OracleError> {
+        match *self {
+            Encoding::Utf8   => str::from_utf8(bytes).map(str::to_string).map_err(|_| {
+                OracleError::new("Text is not valid UTF-8", "Encoding::decode")
+            }),
+            Encoding::Latin1 => Ok(bytes.iter().map(|&b| b as char).collect()),
+            Encoding::Ascii  => match bytes.iter().find(|b| !b.is_ascii()) {
+                Some(b) => Err(OracleError::new(&format!("Byte {:#04x} is not ASCII", b), "Encoding::decode")),
+                None    => Ok(bytes.iter().map(|&b| b as char).collect()),
+            },
+        }
+    }
+
+    /// Whether `byte` starts a character rather than continues one.
+    pub fn is_char_start(&self, byte: u8) -> bool {
+        match *self {
+            Encoding::Utf8 => byte & 0xC0 != 0x80,
+            _              => true,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Encoding, AL32UTF8, UTF8, WE8ISO8859P1, US7ASCII, AL16UTF16};
+
+    #[test]
+    fn maps_charsets() {
+        assert_eq!(Encoding::of(AL32UTF8).unwrap(), Encoding::Utf8);
+        assert_eq!(Encoding::of(UTF8).unwrap(), Encoding::Utf8);
+        assert_eq!(Encoding::of(WE8ISO8859P1).unwrap(), Encoding::Latin1);
+        assert_eq!(Encoding::of(US7ASCII).unwrap(), Encoding::Ascii);
+        assert!(Encoding::of(AL16UTF16).is_err());
+        assert!(Encoding::of(178).is_err());
+    }
+
+    #[test]
+    fn round_trips_latin1() {
+        let text = "Grüße, façade ÿ";
+        let bytes = Encoding::Latin1.encode(text).unwrap();
+        assert_eq!(bytes.len(), text.chars().count());
+        assert_eq!(bytes[2], 0xFC);
+        assert_eq!(Encoding::Latin1.decode(&bytes).unwrap(), text);
+        let all: Vec<u8> = (0..=255).collect();
+        assert_eq!(Encoding::Latin1.encode(&Encoding::Latin1.decode(&all).unwrap()).unwrap(), all);
+    }
+
+    #[test]
+    fn round_trips_ascii() {
+        let text = "SELECT 1 FROM dual\n";
+        let bytes = Encoding::Ascii.encode(text).unwrap();
+        assert_eq!(bytes, text.as_bytes());
+        assert_eq!(Encoding::Ascii.decode(&bytes).unwrap(), text);
+    }
+
+    #[test]
+    fn rejects_unrepresentable_characters() {
+        assert!(Encoding::Latin1.encode("€").is_err());
+        assert!(Encoding::Latin1.encode("ok \u{100}").is_err());
+        assert!(Encoding::Ascii.encode("é").is_err());
+        assert!(Encoding::Ascii.encode("\u{80}").is_err());
+        assert_eq!(Encoding::Utf8.encode("€").unwrap(), "€".as_bytes());
+    }
+
+    #[test]
+    fn rejects_invalid_bytes() {
+        assert!(Encoding::Ascii.decode(&[b'a', 0x80]).is_err());
+        assert!(Encoding::Ascii.decode(&[0xFF]).is_err());
+        assert!(Encoding::Utf8.decode(&[0xC3]).is_err());
+        assert!(Encoding::Utf8.decode(&[0x80, b'a']).is_err());
+        assert_eq!(Encoding::Utf8.decode("ü".as_bytes()).unwrap(), "ü");
+    }
+
+    #[test]
+    fn finds_character_starts() {
+        let bytes = "aé€😀".as_bytes();
+        let starts: Vec<usize> = (0..bytes.len()).filter(|&i| Encoding::Utf8.is_char_start(bytes[i])).collect();
+        assert_eq!(starts, vec![0, 1, 3, 6]);
+        assert!(!Encoding::Utf8.is_char_start(0x80));
+        assert!(!Encoding::Utf8.is_char_start(0xBF));
+        assert!(Encoding::Utf8.is_char_start(0xC0));
+        assert!(Encoding::Latin1.is_char_start(0x80));
+        assert!(Encoding::Ascii.is_char_start(0xBF));
+    }
+}
@@ -0,0 +1,260 @@
+//! Database connection
+
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use {OCIMode, OCICredentialsType, OCIAuthMode, OracleError};
+use {oci_trans_commit, oci_trans_rollback, oci_ping};
+use handle::{Environment, ErrorHandle, Server, ServiceContext, Session};
+use session_pool::{self, SessionPool};
+use connection_pool::ConnectionPool;
+use statement::{Statement, Buffers};
+use statement_cache::{StatementCache, CacheCounters};
+use transaction::Transaction;
+
+/// Connection to an Oracle database.
+///
+/// Owns the whole handle chain and tears it down in reverse order on drop:
+/// the session is ended, the server is detached and then the handles are freed.
+/// A connection taken from a [`SessionPool`](struct.SessionPool.html) is given back to it instead.
+pub struct Connection {
+    origin:     Origin,
+    service:    ServiceContext,
+    error:      ErrorHandle,
+    env:        Environment,
+    autocommit: Cell<bool>,
+    cache:      CacheCounters,
+    retained:   RefCell<HashMap<String, Buffers>>,
+}
+
+/// Where the session of a connection comes from.
+enum Origin {
+    /// Session begun by the connection itself.
+    Login(Login),
+
+    /// Session taken from a session pool.
+    SessionPool(SessionPool),
+}
+
+/// Server and session handles of a connection that logged in itself.
+struct Login {
+    session:  Session,
+    server:   Server,
+    attached: bool,
+    logged:   bool,
+    // Connection pool the server is attached to, kept until the server is freed.
+    #[allow(dead_code)]
+    pool:     Option<ConnectionPool>,
+}
+
+impl Login {
+    fn log_in(&mut self,
+              service: &ServiceContext,
+              error: &ErrorHandle,
+              username: &str,
+              password: &str,
+              db: &str,
+              mode: OCIMode) -> Result<(), OracleError> {
+        self.server.attach(error, db, mode)?;
+        self.attached = true;
+        service.set_server(&self.server, error)?;
+        self.session.set_username(username, error)?;
+        self.session.set_password(password, error)?;
+        service.set_session(&self.session, error)?;
+        self.session.begin(service, error, OCICredentialsType::Rdbms, OCIAuthMode::Default)?;
+        self.logged = true;
+        Ok(())
+    }
+
+    fn log_out(&self, service: &ServiceContext, error: &ErrorHandle) {
+        if self.logged {
+            let _ = self.session.end(service, error);
+        }
+        if self.attached {
+            let _ = self.server.detach(error);
+        }
+    }
+}
+
+impl Connection {
+    /// Attaches to `connect_string` and begins a session for `username`.
+    ///
+    /// Returns the error of the first failing step, any step already done is rolled back.
+    pub fn connect(username: &str,
+                   password: &str,
+                   connect_string: &str) -> Result<Connection, OracleError> {
+        Connection::connect_with_env(&Environment::new()?, username, password, connect_string)
+    }
+
+    /// Same as [`connect`](#method.connect), but in `env`, e.g. one created with an explicit
+    /// character set by [`Environment::builder`](struct.Environment.html#method.builder).
+    pub fn connect_with_env(env: &Environment,
+                            username: &str,
+                            password: &str,
+                            connect_string: &str) -> Result<Connection, OracleError> {
+        let mut conn = Connection {
+            error:      ErrorHandle::new(env)?,
+            origin:     Origin::Login(Login {
+                server:   Server::new(env)?,
+                session:  Session::new(env)?,
+                attached: false,
+                logged:   false,
+                pool:     None,
+            }),
+            service:    ServiceContext::new(env)?,
+            env:        env.clone(),
+            autocommit: Cell::new(false),
+            cache:      CacheCounters::default(),
+            retained:   RefCell::new(HashMap::new()),
+        };
+        if let Origin::Login(ref mut login) = conn.origin {
+            login.log_in(&conn.service, &conn.error, username, password, connect_string, OCIMode::Default)?;
+        }
+        Ok(conn)
+    }
+
+    /// Begins a session for `username` over a physical connection of `pool`.
+    ///
+    /// The server is attached to the pool in `OCIMode::ConnectionPool` and detached when the
+    /// connection is dropped, which leaves the physical connection open in the pool.
+    pub fn connect_with_pool(pool: &ConnectionPool,
+                             username: &str,
+                             password: &str) -> Result<Connection, OracleError> {
+        let env = pool.environment();
+        let mut conn = Connection {
+            error:      ErrorHandle::new(env)?,
+            origin:     Origin::Login(Login {
+                server:   Server::new(env)?,
+                session:  Session::new(env)?,
+                attached: false,
+                logged:   false,
+                pool:     Some(pool.clone()),
+            }),
+            service:    ServiceContext::new(env)?,
+            env:        env.clone(),
+            autocommit: Cell::new(false),
+            cache:      CacheCounters::default(),
+            retained:   RefCell::new(HashMap::new()),
+        };
+        if let Origin::Login(ref mut login) = conn.origin {
+            login.log_in(&conn.service, &conn.error, username, password, pool.name(), OCIMode::ConnectionPool)?;
+        }
+        Ok(conn)
+    }
+
+    /// Environment the connection was created in.
+    pub fn environment(&self) -> &Environment {
+        &self.env
+    }
+
+    /// Error handle used by every call made on the connection.
+    pub fn error_handle(&self) -> &ErrorHandle {
+        &self.error
+    }
+
+    /// Service context of the connection.
+    pub fn service_context(&self) -> &ServiceContext {
+        &self.service
+    }
+
+    /// Removes and returns the warnings of logging in, pinging, ending transactions and executing
+    /// statements, e.g. `ORA-28002: the password will expire` after logging in.
+    pub fn take_warnings(&self) -> Vec<OracleError> {
+        self.error.take_warnings()
+    }
+
+    /// Passes every later warning of the connection and its statements to `handler` instead of
+    /// keeping it for [`take_warnings`](#method.take_warnings).
+    ///
+    /// Warnings of the login are kept, as the handler can only be set once connected.
+    pub fn set_warning_handler<F>(&self, handler: F)
+        where F: Fn(&OracleError) + Send + Sync + 'static {
+        self.error.set_warning_handler(handler)
+    }
+
+    /// Prepares `sql` for execution.
+    pub fn prepare(&self, sql: &str) -> Result<Statement<'_>, OracleError> {
+        Statement::new(self, sql)
+    }
+
+    /// Statement cache of the session, off until its size is set.
+    pub fn statement_cache(&self) -> StatementCache<'_> {
+        StatementCache::new(self)
+    }
+
+    /// Checks that the connection is usable with a round trip to the server
+    /// using [`oci_ping`](fn.oci_ping.html).
+    pub fn ping(&self) -> Result<(), OracleError> {
+        self.error.call(|error| oci_ping(self.service.as_ptr(), error))
+    }
+
+    /// Commits the current transaction with [`oci_trans_commit`](fn.oci_trans_commit.html).
+    pub fn commit(&self) -> Result<(), OracleError> {
+        self.error.call(|error| oci_trans_commit(self.service.as_ptr(), error))
+    }
+
+    /// Rolls back the current transaction with [`oci_trans_rollback`](fn.oci_trans_rollback.html).
+    pub fn rollback(&self) -> Result<(), OracleError> {
+        self.error.call(|error| oci_trans_rollback(self.service.as_ptr(), error))
+    }
+
+    /// Whether statements are executed with `OCI_COMMIT_ON_SUCCESS`.
+    pub fn autocommit(&self) -> bool {
+        self.autocommit.get()
+    }
+
+    /// Turns committing every successful statement on or off, it is off by default.
+    pub fn set_autocommit(&self, autocommit: bool) {
+        self.autocommit.set(autocommit)
+    }
+
+    /// Starts a transaction, which is rolled back when dropped unless committed.
+    ///
+    /// Autocommit is turned off until the transaction ends.
+    pub fn transaction(&self) -> Transaction<'_> {
+        Transaction::new(self)
+    }
+}
+
+impl Drop for Connection {
+    fn drop(&mut self) {
+        match self.origin {
+            Origin::Login(ref login) => login.log_out(&self.service, &self.error),
+            Origin::SessionPool(ref pool) => {
+                // Uncommitted work and cached statements bound to buffers of this connection
+                // must not be left to the next user of the session.
+                if !self.retained.borrow().is_empty() {
+                    let _ = self.statement_cache().set_size(0);
+                }
+                let unusable = match self.rollback() {
+                    Ok(())   => false,
+                    Err(err) => err.is_connection_lost(),
+                };
+                session_pool::release(pool, &self.service, &self.error, unusable);
+            },
+        }
+    }
+}
+
+/// Connection on the session in `service` taken from `pool`.
+pub fn pooled(pool: SessionPool, service: ServiceContext, error: ErrorHandle) -> Connection {
+    let env = pool.environment().clone();
+    Connection {
+        origin:     Origin::SessionPool(pool),
+        service,
+        error,
+        env,
+        autocommit: Cell::new(false),
+        cache:      CacheCounters::default(),
+        retained:   RefCell::new(HashMap::new()),
+    }
+}
+
+/// Hits and misses of the statement cache of `conn`.
+pub fn cache_counters(conn: &Connection) -> &CacheCounters {
+    &conn.cache
+}
+
+/// Bind buffers of the statements of `conn` put back into the statement cache, by key.
+pub fn retained_buffers(conn: &Connection) -> &RefCell<HashMap<String, Buffers>> {
+    &conn.retained
+}
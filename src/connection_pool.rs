@@ -0,0 +1,255 @@
+//! Connection pooling
+
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use {c_void, c_uchar, c_uint, OCICPool, OCIHandleType, OCIAttribute, OCIMode, OracleError};
+use {oci_handle_alloc, oci_handle_free, oci_attr_set, oci_handle_attr_get, oci_connection_pool_create,
+     oci_connection_pool_destroy};
+use handle::{Environment, ErrorHandle, Server, ServiceContext};
+
+struct ConnectionPoolHandle {
+    handle:  *mut OCICPool,
+    name:    String,
+    created: bool,
+    error:   Mutex<ErrorHandle>,
+    env:     Environment,
+}
+
+impl Drop for ConnectionPoolHandle {
+    fn drop(&mut self) {
+        if self.created {
+            let error = self.error.lock().unwrap_or_else(|err| err.into_inner());
+            let _ = oci_connection_pool_destroy(self.handle, error.as_ptr());
+        }
+        let _ = oci_handle_free(self.handle as *mut c_void, OCIHandleType::ConnectionPool);
+    }
+}
+
+// The pool is created in a threaded environment, which makes OCI serialise the calls on it.
+unsafe impl Send for ConnectionPoolHandle {}
+unsafe impl Sync for ConnectionPoolHandle {}
+
+/// Pool of physical connections created with
+/// [`oci_connection_pool_create`](fn.oci_connection_pool_create.html), which many sessions are
+/// multiplexed over.
+///
+/// Cloning is cheap and shares the same pool, which is destroyed once the last clone and every
+/// server attached to it are dropped.
+#[derive(Clone)]
+pub struct ConnectionPool {
+    inner: Arc<ConnectionPoolHandle>,
+}
+
+impl ConnectionPool {
+    /// Starts configuring a pool of connections to `connect_string`, opened as `username`.
+    pub fn builder(username: &str, password: &str, connect_string: &str) -> ConnectionPoolBuilder {
+        ConnectionPoolBuilder {
+            username:       username.to_string(),
+            password:       password.to_string(),
+            connect_string: connect_string.to_string(),
+            env:            None,
+            min:            1,
+            max:            10,
+            increment:      1,
+            timeout:        None,
+            no_wait:        false,
+        }
+    }
+
+    /// Name of the pool, which servers attach to in `OCIMode::ConnectionPool`.
+    pub fn name(&self) -> &str {
+        &self.inner.name
+    }
+
+    /// Environment the pool was created in.
+    pub fn environment(&self) -> &Environment {
+        &self.inner.env
+    }
+
+    /// Attaches a new server handle to the pool and sets it on a new service context.
+    ///
+    /// A session still has to be begun on the service context, as done by
+    /// [`Connection::connect_with_pool`](struct.Connection.html#method.connect_with_pool).
+    pub fn attach(&self) -> Result<PooledServer, OracleError> {
+        let env = &self.inner.env;
+        let mut pooled = PooledServer {
+            service:  ServiceContext::new(env)?,
+            server:   Server::new(env)?,
+            error:    ErrorHandle::new(env)?,
+            attached: false,
+            pool:     self.clone(),
+        };
+        pooled.server.attach(&pooled.error, &self.inner.name, OCIMode::ConnectionPool)?;
+        pooled.attached = true;
+        pooled.service.set_server(&pooled.server, &pooled.error)?;
+        Ok(pooled)
+    }
+
+    /// Number of connections in use.
+    pub fn busy_count(&self) -> Result<u32, OracleError> {
+        self.count(OCIAttribute::ConnectionPoolBusyCount)
+    }
+
+    /// Number of open connections, busy or idle.
+    pub fn open_count(&self) -> Result<u32, OracleError> {
+        self.count(OCIAttribute::ConnectionPoolOpenCount)
+    }
+
+    fn count(&self, attr_type: OCIAttribute) -> Result<u32, OracleError> {
+        let error = self.inner.error.lock().unwrap_or_else(|err| err.into_inner());
+        let (count, _) = oci_handle_attr_get::<c_uint>(self.inner.handle as *mut c_void,
+                                                       OCIHandleType::ConnectionPool,
+                                                       attr_type,
+                                                       error.as_ptr())?;
+        Ok(count)
+    }
+}
+
+/// Server handle attached to a [`ConnectionPool`](struct.ConnectionPool.html) and the service
+/// context it is set on.
+///
+/// The server is detached on drop, which leaves the physical connection to the pool.
+pub struct PooledServer {
+    service:  ServiceContext,
+    server:   Server,
+    error:    ErrorHandle,
+    attached: bool,
+    #[allow(dead_code)]
+    pool:     ConnectionPool,
+}
+
+impl PooledServer {
+    /// Server handle attached to the pool.
+    pub fn server(&self) -> &Server {
+        &self.server
+    }
+
+    /// Service context the server is set on.
+    pub fn service_context(&self) -> &ServiceContext {
+        &self.service
+    }
+
+    /// Error handle used to attach and detach the server.
+    pub fn error_handle(&self) -> &ErrorHandle {
+        &self.error
+    }
+}
+
+impl Drop for PooledServer {
+    fn drop(&mut self) {
+        if self.attached {
+            let _ = self.server.detach(&self.error);
+        }
+    }
+}
+
+/// Configuration of a new [`ConnectionPool`](struct.ConnectionPool.html).
+#[derive(Clone)]
+pub struct ConnectionPoolBuilder {
+    username:       String,
+    password:       String,
+    connect_string: String,
+    env:            Option<Environment>,
+    min:            u32,
+    max:            u32,
+    increment:      u32,
+    timeout:        Option<Duration>,
+    no_wait:        bool,
+}
+
+impl ConnectionPoolBuilder {
+    /// Creates the pool in `env` rather than in a new environment.
+    pub fn environment(mut self, env: &Environment) -> ConnectionPoolBuilder {
+        self.env = Some(env.clone());
+        self
+    }
+
+    /// Number of connections opened with the pool and kept open, 1 by default.
+    pub fn min(mut self, min: u32) -> ConnectionPoolBuilder {
+        self.min = min;
+        self
+    }
+
+    /// Maximum number of open connections, 10 by default.
+    pub fn max(mut self, max: u32) -> ConnectionPoolBuilder {
+        self.max = max;
+        self
+    }
+
+    /// Number of connections opened at once when more are needed, 1 by default.
+    pub fn increment(mut self, increment: u32) -> ConnectionPoolBuilder {
+        self.increment = increment;
+        self
+    }
+
+    /// Closes connections above the minimum once they are idle for `timeout`.
+    pub fn timeout(mut self, timeout: Duration) -> ConnectionPoolBuilder {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Makes calls fail rather than wait when every connection is busy and the pool is at its
+    /// maximum, by default they wait.
+    pub fn no_wait(mut self, no_wait: bool) -> ConnectionPoolBuilder {
+        self.no_wait = no_wait;
+        self
+    }
+
+    /// Creates the pool with [`oci_connection_pool_create`](fn.oci_connection_pool_create.html)
+    /// and opens its minimum number of connections.
+    pub fn build(self) -> Result<ConnectionPool, OracleError> {
+        if self.max == 0 || self.min > self.max {
+            return Err(OracleError::new(&format!("Invalid connection pool size {}..{}", self.min, self.max),
+                                        "ConnectionPoolBuilder::build"));
+        }
+        let env = match self.env {
+            Some(env) => env,
+            None      => Environment::new()?,
+        };
+        let error = ErrorHandle::new(&env)?;
+        let handle = oci_handle_alloc(env.as_ptr(), OCIHandleType::ConnectionPool)? as *mut OCICPool;
+        let mut pool = ConnectionPoolHandle {
+            handle,
+            name:    String::new(),
+            created: false,
+            error:   Mutex::new(error),
+            env,
+        };
+        let encoding = pool.env.encoding();
+        {
+            let error = pool.error.get_mut().unwrap_or_else(|err| err.into_inner());
+            let name = oci_connection_pool_create(pool.env.as_ptr(),
+                                                  error.as_ptr(),
+                                                  handle,
+                                                  &encoding.encode(&self.connect_string)?,
+                                                  self.min,
+                                                  self.max,
+                                                  self.increment,
+                                                  &encoding.encode(&self.username)?,
+                                                  &encoding.encode(&self.password)?)?;
+            pool.created = true;
+            pool.name = encoding.decode(&name)?;
+            if let Some(timeout) = self.timeout {
+                let mut seconds = timeout.as_secs().min(c_uint::MAX as u64) as c_uint;
+                set_attr(handle, &mut seconds, OCIAttribute::ConnectionPoolTimeout, error)?;
+            }
+            if self.no_wait {
+                // Only the presence of the attribute matters, not its value.
+                let mut no_wait: c_uchar = 1;
+                set_attr(handle, &mut no_wait, OCIAttribute::ConnectionPoolNoWait, error)?;
+            }
+        }
+        Ok(ConnectionPool { inner: Arc::new(pool) })
+    }
+}
+
+fn set_attr<T>(handle: *mut OCICPool,
+               value: &mut T,
+               attr_type: OCIAttribute,
+               error: &ErrorHandle) -> Result<(), OracleError> {
+    oci_attr_set(handle as *mut c_void,
+                 OCIHandleType::ConnectionPool,
+                 value as *mut T as *mut c_void,
+                 attr_type,
+                 error.as_ptr())
+}
@@ -0,0 +1,506 @@
+//! Oracle `DATE` and `TIMESTAMP` values
+
+use {OCIEnv, OCIError, OCIDateTime, OCIDataType, OracleError};
+use {oci_date_time_construct, oci_date_time_get_date, oci_date_time_get_time,
+     oci_date_time_get_time_zone_offset, oci_date_time_get_time_zone_name};
+use value::{SqlValue, ToSql, FromSql};
+
+/// Largest offset from UTC of a time zone in minutes, 14:00.
+const MAX_TZ_OFFSET: i32 = 14 * 60;
+
+/// Oracle `DATE`: date and time with second precision.
+///
+/// Exchanged as `SQLT_DAT` in the 7-byte internal format: century + 100, year of the century + 100,
+/// month, day, hour + 1, minute + 1 and second + 1.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct OracleDate {
+    /// Year, negative for BC dates.
+    pub year:   i32,
+    /// Month, 1 to 12.
+    pub month:  u32,
+    /// Day of the month, 1 to 31.
+    pub day:    u32,
+    /// Hour, 0 to 23.
+    pub hour:   u32,
+    /// Minute, 0 to 59.
+    pub minute: u32,
+    /// Second, 0 to 59.
+    pub second: u32,
+}
+
+impl OracleDate {
+    /// Creates a date with the time of day.
+    pub fn new(year: i32, month: u32, day: u32, hour: u32, minute: u32, second: u32) -> OracleDate {
+        OracleDate { year, month, day, hour, minute, second }
+    }
+
+    /// Reads the 7-byte internal format.
+    pub fn from_bytes(bytes: &[u8]) -> Result<OracleDate, OracleError> {
+        if bytes.len() < 7 {
+            return Err(OracleError::new("Invalid DATE length", "OracleDate::from_bytes"));
+        }
+        Ok(OracleDate {
+            year:   (bytes[0] as i32 - 100) * 100 + (bytes[1] as i32 - 100),
+            month:  bytes[2] as u32,
+            day:    bytes[3] as u32,
+            hour:   (bytes[4] as u32).saturating_sub(1),
+            minute: (bytes[5] as u32).saturating_sub(1),
+            second: (bytes[6] as u32).saturating_sub(1),
+        })
+    }
+
+    /// Writes the 7-byte internal format.
+    pub fn to_bytes(&self) -> Result<[u8; 7], OracleError> {
+        self.validate()?;
+        Ok([
+            (self.year / 100 + 100) as u8,
+            (self.year % 100 + 100) as u8,
+            self.month as u8,
+            self.day as u8,
+            self.hour as u8 + 1,
+            self.minute as u8 + 1,
+            self.second as u8 + 1,
+        ])
+    }
+
+    fn validate(&self) -> Result<(), OracleError> {
+        if self.year < -4712 || self.year > 9999 || self.year == 0
+           || self.month < 1 || self.month > 12 || self.day < 1 || self.day > 31
+           || self.hour > 23 || self.minute > 59 || self.second > 59 {
+            return Err(OracleError::new(&format!("{:?} is out of range of Oracle DATE", self),
+                                        "OracleDate::validate"));
+        }
+        Ok(())
+    }
+}
+
+/// Oracle `TIMESTAMP`, `TIMESTAMP WITH TIME ZONE` and `TIMESTAMP WITH LOCAL TIME ZONE`.
+///
+/// Carried in a [`SqlValue`](struct.SqlValue.html) as the 7-byte `DATE` format followed by the
+/// big-endian 4-byte nanosecond and, for values with a time zone, the offset hour + 20,
+/// the offset minute + 60 and the UTF-8 region name if there is one.
+/// Date and time are local to the time zone.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct OracleTimestamp {
+    /// Year, negative for BC dates.
+    pub year:       i32,
+    /// Month, 1 to 12.
+    pub month:      u32,
+    /// Day of the month, 1 to 31.
+    pub day:        u32,
+    /// Hour, 0 to 23.
+    pub hour:       u32,
+    /// Minute, 0 to 59.
+    pub minute:     u32,
+    /// Second, 0 to 59.
+    pub second:     u32,
+    /// Fractional second in nanoseconds.
+    pub nanosecond: u32,
+    /// Offset from UTC in minutes, `None` for a `TIMESTAMP` without time zone.
+    pub tz_offset:  Option<i32>,
+    /// Time zone region like `Europe/Paris`, if the value has one.
+    pub tz_region:  Option<String>,
+}
+
+impl OracleTimestamp {
+    /// Creates a timestamp without time zone.
+    pub fn new(year: i32, month: u32, day: u32,
+               hour: u32, minute: u32, second: u32, nanosecond: u32) -> OracleTimestamp {
+        OracleTimestamp {
+            year, month, day, hour, minute, second, nanosecond, tz_offset: None, tz_region: None
+        }
+    }
+
+    /// Sets the offset from UTC in minutes, -840 to 840.
+    pub fn with_tz_offset(mut self, minutes: i32) -> OracleTimestamp {
+        self.tz_offset = Some(minutes);
+        self
+    }
+
+    /// Sets the time zone region, the offset is resolved by the database.
+    pub fn with_tz_region(mut self, region: &str) -> OracleTimestamp {
+        self.tz_offset = Some(self.tz_offset.unwrap_or(0));
+        self.tz_region = Some(region.to_string());
+        self
+    }
+
+    /// Date and time truncated to seconds.
+    pub fn date(&self) -> OracleDate {
+        OracleDate::new(self.year, self.month, self.day, self.hour, self.minute, self.second)
+    }
+
+    /// Time zone as accepted by `OCIDateTimeConstruct()`: the region or the offset like `+02:00`.
+    fn time_zone(&self) -> Option<String> {
+        match (self.tz_region.as_ref(), self.tz_offset) {
+            (Some(region), _)  => Some(region.clone()),
+            (None, Some(tz))   => Some(format!("{}{:02}:{:02}",
+                                               if tz < 0 { '-' } else { '+' },
+                                               tz.abs() / 60, tz.abs() % 60)),
+            (None, None)       => None,
+        }
+    }
+
+    fn from_bytes(bytes: &[u8]) -> Result<OracleTimestamp, OracleError> {
+        if bytes.len() != 11 && bytes.len() < 13 {
+            return Err(OracleError::new("Invalid TIMESTAMP length", "OracleTimestamp::from_bytes"));
+        }
+        let date = OracleDate::from_bytes(bytes)?;
+        let mut timestamp = OracleTimestamp::new(
+            date.year, date.month, date.day, date.hour, date.minute, date.second,
+            u32::from_be_bytes([bytes[7], bytes[8], bytes[9], bytes[10]]));
+        if bytes.len() >= 13 {
+            timestamp.tz_offset = Some((bytes[11] as i32 - 20) * 60 + (bytes[12] as i32 - 60));
+            if bytes.len() > 13 {
+                timestamp.tz_region = Some(String::from_utf8_lossy(&bytes[13..]).into_owned());
+            }
+        }
+        Ok(timestamp)
+    }
+
+    fn to_bytes(&self) -> Result<Vec<u8>, OracleError> {
+        if self.nanosecond > 999_999_999
+           || self.tz_offset.is_some_and(|tz| !(-MAX_TZ_OFFSET..=MAX_TZ_OFFSET).contains(&tz)) {
+            return Err(OracleError::new(&format!("{:?} is out of range of Oracle TIMESTAMP", self),
+                                        "OracleTimestamp::to_bytes"));
+        }
+        let mut bytes = self.date().to_bytes()?.to_vec();
+        bytes.extend_from_slice(&self.nanosecond.to_be_bytes());
+        if let Some(tz) = self.tz_offset {
+            bytes.push((tz / 60 + 20) as u8);
+            bytes.push((tz % 60 + 60) as u8);
+            if let Some(ref region) = self.tz_region {
+                bytes.extend_from_slice(region.as_bytes());
+            }
+        }
+        Ok(bytes)
+    }
+}
+
+/// Bound as `SQLT_DAT`.
+impl ToSql for OracleDate {
+    fn to_sql(&self) -> Result<SqlValue, OracleError> {
+        Ok(SqlValue::new(OCIDataType::InternalDate, self.to_bytes()?.to_vec()))
+    }
+}
+
+impl FromSql for OracleDate {
+    fn from_sql(value: &SqlValue) -> Result<OracleDate, OracleError> {
+        match value.data_type() {
+            OCIDataType::InternalDate if !value.is_null() => OracleDate::from_bytes(value.as_bytes()),
+            _ => OracleTimestamp::from_sql(value).map(|timestamp| timestamp.date()),
+        }
+    }
+}
+
+/// Bound as `SQLT_TIMESTAMP_TZ` when it has a time zone and as `SQLT_TIMESTAMP` otherwise.
+impl ToSql for OracleTimestamp {
+    fn to_sql(&self) -> Result<SqlValue, OracleError> {
+        let data_type = match self.tz_offset {
+            Some(_) => OCIDataType::TimestampWithTz,
+            None    => OCIDataType::Timestamp,
+        };
+        Ok(SqlValue::new(data_type, self.to_bytes()?))
+    }
+}
+
+impl FromSql for OracleTimestamp {
+    fn from_sql(value: &SqlValue) -> Result<OracleTimestamp, OracleError> {
+        if value.is_null() {
+            return Err(OracleError::new("Cannot convert NULL to OracleTimestamp", "FromSql::from_sql"));
+        }
+        match value.data_type() {
+            OCIDataType::Timestamp | OCIDataType::TimestampWithTz | OCIDataType::TimestampWithLocalTz =>
+                OracleTimestamp::from_bytes(value.as_bytes()),
+            OCIDataType::InternalDate => {
+                let date = OracleDate::from_bytes(value.as_bytes())?;
+                Ok(OracleTimestamp::new(date.year, date.month, date.day,
+                                        date.hour, date.minute, date.second, 0))
+            },
+            data_type => Err(OracleError::new(&format!("Cannot convert {:?} to OracleTimestamp", data_type),
+                                              "FromSql::from_sql")),
+        }
+    }
+}
+
+/// Reads a fetched `OCIDateTime` descriptor into a value of `data_type`.
+pub fn read_timestamp(envh: *mut OCIEnv,
+                      error_handle: *mut OCIError,
+                      datetime: *mut OCIDateTime,
+                      data_type: OCIDataType) -> Result<SqlValue, OracleError> {
+    let (year, month, day) = oci_date_time_get_date(envh, error_handle, datetime)?;
+    let (hour, minute, second, nanosecond) = oci_date_time_get_time(envh, error_handle, datetime)?;
+    let mut timestamp = OracleTimestamp::new(year as i32, month as u32, day as u32,
+                                             hour as u32, minute as u32, second as u32, nanosecond);
+    if data_type != OCIDataType::Timestamp {
+        let (tz_hour, tz_minute) = oci_date_time_get_time_zone_offset(envh, error_handle, datetime)?;
+        timestamp.tz_offset = Some(tz_hour as i32 * 60 + tz_minute as i32);
+        let name = oci_date_time_get_time_zone_name(envh, error_handle, datetime)?;
+        if !name.starts_with('+') && !name.starts_with('-') {
+            timestamp.tz_region = Some(name);
+        }
+    }
+    Ok(SqlValue::new(data_type, timestamp.to_bytes()?))
+}
+
+/// Fills an `OCIDateTime` descriptor from a timestamp `value`.
+pub fn construct_timestamp(envh: *mut OCIEnv,
+                           error_handle: *mut OCIError,
+                           datetime: *mut OCIDateTime,
+                           value: &SqlValue) -> Result<(), OracleError> {
+    let timestamp = OracleTimestamp::from_sql(value)?;
+    // The local time zone of the session is implied for `TIMESTAMP WITH LOCAL TIME ZONE`.
+    let time_zone = match value.data_type() {
+        OCIDataType::TimestampWithTz => timestamp.time_zone(),
+        _                            => None,
+    };
+    oci_date_time_construct(envh, error_handle, datetime,
+                            (timestamp.year as i16, timestamp.month as u8, timestamp.day as u8),
+                            (timestamp.hour as u8, timestamp.minute as u8, timestamp.second as u8,
+                             timestamp.nanosecond),
+                            time_zone.as_deref())
+}
+
+#[cfg(feature = "chrono")]
+mod chrono_support {
+    use chrono::{NaiveDate, NaiveDateTime, DateTime, FixedOffset, Utc, TimeZone, Datelike, Timelike};
+    use OracleError;
+    use value::{SqlValue, ToSql, FromSql};
+    use super::{OracleDate, OracleTimestamp};
+
+    fn invalid(target: &str) -> OracleError {
+        OracleError::new(&format!("Value is out of range of {}", target), "FromSql::from_sql")
+    }
+
+    fn naive(timestamp: &OracleTimestamp) -> Result<NaiveDateTime, OracleError> {
+        NaiveDate::from_ymd_opt(timestamp.year, timestamp.month, timestamp.day)
+            .and_then(|date| date.and_hms_nano_opt(timestamp.hour, timestamp.minute,
+                                                   timestamp.second, timestamp.nanosecond))
+            .ok_or_else(|| invalid("NaiveDateTime"))
+    }
+
+    fn timestamp(value: &NaiveDateTime) -> OracleTimestamp {
+        OracleTimestamp::new(value.year(), value.month(), value.day(),
+                             value.hour(), value.minute(), value.second(), value.nanosecond())
+    }
+
+    impl ToSql for NaiveDateTime {
+        fn to_sql(&self) -> Result<SqlValue, OracleError> {
+            timestamp(self).to_sql()
+        }
+    }
+
+    /// The time zone of the value, if any, is ignored.
+    impl FromSql for NaiveDateTime {
+        fn from_sql(value: &SqlValue) -> Result<NaiveDateTime, OracleError> {
+            naive(&OracleTimestamp::from_sql(value)?)
+        }
+    }
+
+    impl ToSql for NaiveDate {
+        fn to_sql(&self) -> Result<SqlValue, OracleError> {
+            OracleDate::new(self.year(), self.month(), self.day(), 0, 0, 0).to_sql()
+        }
+    }
+
+    /// The time of day is ignored.
+    impl FromSql for NaiveDate {
+        fn from_sql(value: &SqlValue) -> Result<NaiveDate, OracleError> {
+            let date = OracleDate::from_sql(value)?;
+            NaiveDate::from_ymd_opt(date.year, date.month, date.day).ok_or_else(|| invalid("NaiveDate"))
+        }
+    }
+
+    impl ToSql for DateTime<FixedOffset> {
+        fn to_sql(&self) -> Result<SqlValue, OracleError> {
+            timestamp(&self.naive_local()).with_tz_offset(self.offset().local_minus_utc() / 60).to_sql()
+        }
+    }
+
+    /// Values without time zone are taken as UTC.
+    impl FromSql for DateTime<FixedOffset> {
+        fn from_sql(value: &SqlValue) -> Result<DateTime<FixedOffset>, OracleError> {
+            let timestamp = OracleTimestamp::from_sql(value)?;
+            FixedOffset::east_opt(timestamp.tz_offset.unwrap_or(0) * 60)
+                .and_then(|offset| naive(&timestamp).ok()
+                    .and_then(|naive| offset.from_local_datetime(&naive).single()))
+                .ok_or_else(|| invalid("DateTime<FixedOffset>"))
+        }
+    }
+
+    impl ToSql for DateTime<Utc> {
+        fn to_sql(&self) -> Result<SqlValue, OracleError> {
+            timestamp(&self.naive_utc()).with_tz_offset(0).to_sql()
+        }
+    }
+
+    /// Values without time zone are taken as UTC.
+    impl FromSql for DateTime<Utc> {
+        fn from_sql(value: &SqlValue) -> Result<DateTime<Utc>, OracleError> {
+            DateTime::<FixedOffset>::from_sql(value).map(|datetime| datetime.with_timezone(&Utc))
+        }
+    }
+}
+
+#[cfg(feature = "time")]
+mod time_support {
+    use std::convert::TryFrom;
+    use time::{Date, Month, Time, PrimitiveDateTime, OffsetDateTime, UtcOffset};
+    use OracleError;
+    use value::{SqlValue, ToSql, FromSql};
+    use super::{OracleDate, OracleTimestamp};
+
+    fn invalid(target: &str) -> OracleError {
+        OracleError::new(&format!("Value is out of range of {}", target), "FromSql::from_sql")
+    }
+
+    fn date(year: i32, month: u32, day: u32) -> Option<Date> {
+        Month::try_from(month as u8).ok()
+            .and_then(|month| Date::from_calendar_date(year, month, day as u8).ok())
+    }
+
+    fn primitive(timestamp: &OracleTimestamp) -> Result<PrimitiveDateTime, OracleError> {
+        let time = Time::from_hms_nano(timestamp.hour as u8, timestamp.minute as u8,
+                                       timestamp.second as u8, timestamp.nanosecond).ok();
+        date(timestamp.year, timestamp.month, timestamp.day)
+            .and_then(|date| time.map(|time| PrimitiveDateTime::new(date, time)))
+            .ok_or_else(|| invalid("PrimitiveDateTime"))
+    }
+
+    fn timestamp(value: &PrimitiveDateTime) -> OracleTimestamp {
+        OracleTimestamp::new(value.year(), u8::from(value.month()) as u32, value.day() as u32,
+                             value.hour() as u32, value.minute() as u32, value.second() as u32,
+                             value.nanosecond())
+    }
+
+    impl ToSql for PrimitiveDateTime {
+        fn to_sql(&self) -> Result<SqlValue, OracleError> {
+            timestamp(self).to_sql()
+        }
+    }
+
+    /// The time zone of the value, if any, is ignored.
+    impl FromSql for PrimitiveDateTime {
+        fn from_sql(value: &SqlValue) -> Result<PrimitiveDateTime, OracleError> {
+            primitive(&OracleTimestamp::from_sql(value)?)
+        }
+    }
+
+    impl ToSql for Date {
+        fn to_sql(&self) -> Result<SqlValue, OracleError> {
+            OracleDate::new(self.year(), u8::from(self.month()) as u32, self.day() as u32, 0, 0, 0).to_sql()
+        }
+    }
+
+    /// The time of day is ignored.
+    impl FromSql for Date {
+        fn from_sql(value: &SqlValue) -> Result<Date, OracleError> {
+            let value = OracleDate::from_sql(value)?;
+            date(value.year, value.month, value.day).ok_or_else(|| invalid("Date"))
+        }
+    }
+
+    impl ToSql for OffsetDateTime {
+        fn to_sql(&self) -> Result<SqlValue, OracleError> {
+            timestamp(&PrimitiveDateTime::new(self.date(), self.time()))
+                .with_tz_offset(self.offset().whole_seconds() / 60)
+                .to_sql()
+        }
+    }
+
+    /// Values without time zone are taken as UTC.
+    impl FromSql for OffsetDateTime {
+        fn from_sql(value: &SqlValue) -> Result<OffsetDateTime, OracleError> {
+            let timestamp = OracleTimestamp::from_sql(value)?;
+            let offset = UtcOffset::from_whole_seconds(timestamp.tz_offset.unwrap_or(0) * 60)
+                .map_err(|_| invalid("OffsetDateTime"))?;
+            Ok(primitive(&timestamp)?.assume_offset(offset))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use OCIDataType;
+    use value::{SqlValue, ToSql, FromSql};
+    use super::{OracleDate, OracleTimestamp};
+
+    #[test]
+    fn encodes_dates() {
+        assert_eq!(OracleDate::new(2024, 2, 29, 13, 45, 30).to_bytes().unwrap(), [120, 124, 2, 29, 14, 46, 31]);
+        assert_eq!(OracleDate::new(1, 1, 1, 0, 0, 0).to_bytes().unwrap(), [100, 101, 1, 1, 1, 1, 1]);
+        assert_eq!(OracleDate::new(9999, 12, 31, 23, 59, 59).to_bytes().unwrap(), [199, 199, 12, 31, 24, 60, 60]);
+    }
+
+    #[test]
+    fn encodes_bc_dates() {
+        assert_eq!(OracleDate::new(-1, 1, 1, 0, 0, 0).to_bytes().unwrap(), [100, 99, 1, 1, 1, 1, 1]);
+        assert_eq!(OracleDate::new(-4712, 1, 1, 0, 0, 0).to_bytes().unwrap(), [53, 88, 1, 1, 1, 1, 1]);
+        for &year in &[-4712, -101, -100, -1, 1, 100, 1999, 9999] {
+            let date = OracleDate::new(year, 6, 15, 12, 30, 45);
+            assert_eq!(OracleDate::from_bytes(&date.to_bytes().unwrap()).unwrap(), date);
+        }
+    }
+
+    #[test]
+    fn rejects_invalid_dates() {
+        for date in &[OracleDate::new(0, 1, 1, 0, 0, 0),
+                      OracleDate::new(-4713, 1, 1, 0, 0, 0),
+                      OracleDate::new(10000, 1, 1, 0, 0, 0),
+                      OracleDate::new(2000, 13, 1, 0, 0, 0),
+                      OracleDate::new(2000, 1, 32, 0, 0, 0),
+                      OracleDate::new(2000, 1, 1, 24, 0, 0),
+                      OracleDate::new(2000, 1, 1, 0, 60, 0),
+                      OracleDate::new(2000, 1, 1, 0, 0, 60)] {
+            assert!(date.to_bytes().is_err(), "{:?}", date);
+        }
+        assert!(OracleDate::from_bytes(&[120, 124, 2, 29, 14, 46]).is_err());
+    }
+
+    #[test]
+    fn encodes_timestamps() {
+        let timestamp = OracleTimestamp::new(2024, 2, 29, 13, 45, 30, 123_456_789);
+        let value = timestamp.to_sql().unwrap();
+        assert_eq!(value.data_type(), OCIDataType::Timestamp);
+        assert_eq!(value.as_bytes(), &[120, 124, 2, 29, 14, 46, 31, 0x07, 0x5B, 0xCD, 0x15]);
+        assert_eq!(OracleTimestamp::from_sql(&value).unwrap(), timestamp);
+        let bc = OracleTimestamp::new(-44, 3, 15, 0, 0, 0, 0);
+        assert_eq!(OracleTimestamp::from_sql(&bc.to_sql().unwrap()).unwrap(), bc);
+    }
+
+    #[test]
+    fn encodes_time_zones() {
+        let positive = OracleTimestamp::new(2024, 1, 1, 0, 0, 0, 0).with_tz_offset(330);
+        let value = positive.to_sql().unwrap();
+        assert_eq!(value.data_type(), OCIDataType::TimestampWithTz);
+        assert_eq!(&value.as_bytes()[11..], &[25, 90]);
+        assert_eq!(OracleTimestamp::from_sql(&value).unwrap(), positive);
+        let negative = OracleTimestamp::new(2024, 1, 1, 0, 0, 0, 0).with_tz_offset(-210);
+        let value = negative.to_sql().unwrap();
+        assert_eq!(&value.as_bytes()[11..], &[17, 30]);
+        assert_eq!(OracleTimestamp::from_sql(&value).unwrap(), negative);
+        let region = OracleTimestamp::new(2024, 1, 1, 0, 0, 0, 0).with_tz_region("Europe/Paris");
+        let value = region.to_sql().unwrap();
+        assert_eq!(&value.as_bytes()[11..], b"\x14\x3CEurope/Paris");
+        assert_eq!(OracleTimestamp::from_sql(&value).unwrap(), region);
+    }
+
+    #[test]
+    fn rejects_invalid_timestamps() {
+        for &tz in &[841, -841, i32::MAX, i32::MIN] {
+            assert!(OracleTimestamp::new(2024, 1, 1, 0, 0, 0, 0).with_tz_offset(tz).to_sql().is_err(), "{}", tz);
+        }
+        for &tz in &[840, -840] {
+            assert!(OracleTimestamp::new(2024, 1, 1, 0, 0, 0, 0).with_tz_offset(tz).to_sql().is_ok(), "{}", tz);
+        }
+        assert!(OracleTimestamp::new(2024, 1, 1, 0, 0, 0, 1_000_000_000).to_sql().is_err());
+        assert!(OracleTimestamp::from_sql(&SqlValue::new(OCIDataType::Timestamp, vec![120, 124, 2, 29])).is_err());
+    }
+
+    #[test]
+    fn converts_dates_to_timestamps() {
+        let date = OracleDate::new(-4712, 1, 1, 23, 59, 59);
+        let timestamp = OracleTimestamp::from_sql(&date.to_sql().unwrap()).unwrap();
+        assert_eq!(timestamp, OracleTimestamp::new(-4712, 1, 1, 23, 59, 59, 0));
+        assert_eq!(timestamp.date(), date);
+    }
+}
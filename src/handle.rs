@@ -0,0 +1,557 @@
+//! Owned wrappers around OCI handles.
+//!
+//! Every wrapper frees its handle with `OCIHandleFree()` on drop and keeps the
+//! [`Environment`](struct.Environment.html) it was allocated from alive, so the environment
+//! handle is always released last.
+
+use std::cell::{Cell, RefCell};
+use std::ffi::CString;
+use std::fmt;
+use std::mem;
+use std::sync::{Arc, Mutex};
+use {c_void, c_ushort, OCIEnv, OCIMemoryCallbacks, OCIError, OCIServer, OCISvcCtx, OCISession, OCIMode, OCIHandleType,
+     OCIAttribute, OCICredentialsType, OCIAuthMode, OCIDescriptorType, OracleError};
+use {oci_env_nls_create, oci_nls_charset_name_to_id, oci_handle_alloc, oci_handle_free,
+     oci_handle_attr_get, oci_error_get, oci_server_attach, oci_server_detach, oci_attr_set,
+     oci_session_begin, oci_session_end, oci_descriptor_alloc, oci_descriptor_free};
+use allocator::{self, OciAllocator};
+use charset::{self, Charset, Encoding};
+
+struct EnvironmentHandle {
+    handle:    *mut OCIEnv,
+    charset:   u16,
+    ncharset:  u16,
+    encoding:  Encoding,
+    // Dropped after the handle is freed, as OCI frees its memory with it.
+    #[allow(dead_code)]
+    allocator: Option<allocator::Context>,
+}
+
+impl EnvironmentHandle {
+    fn create(mode: OCIMode,
+              charset: u16,
+              ncharset: u16,
+              allocator: Option<Arc<dyn OciAllocator>>) -> Result<EnvironmentHandle, OracleError> {
+        let allocator = allocator.map(Box::new);
+        let memory = allocator.as_ref().map(|allocator| OCIMemoryCallbacks {
+            context: &**allocator as *const Arc<dyn OciAllocator> as *mut c_void,
+            malloc:  allocator::malloc,
+            realloc: allocator::realloc,
+            free:    allocator::free,
+        });
+        let handle = oci_env_nls_create(mode, charset, ncharset, memory)?;
+        Ok(EnvironmentHandle { handle, charset, ncharset, encoding: Encoding::Utf8, allocator })
+    }
+
+    /// Reads a character set id attribute of the environment with a temporary error handle.
+    fn charset_attr(&self, attr_type: OCIAttribute) -> Result<u16, OracleError> {
+        let error = oci_handle_alloc(self.handle, OCIHandleType::Error)? as *mut OCIError;
+        let result = oci_handle_attr_get::<c_ushort>(self.handle as *mut c_void,
+                                                     OCIHandleType::Environment,
+                                                     attr_type,
+                                                     error);
+        let _ = oci_handle_free(error as *mut c_void, OCIHandleType::Error);
+        result.map(|(id, _)| id)
+    }
+}
+
+impl Drop for EnvironmentHandle {
+    fn drop(&mut self) {
+        let _ = oci_handle_free(self.handle as *mut c_void, OCIHandleType::Environment);
+    }
+}
+
+// The environment is always created in `OCI_THREADED` mode, so OCI protects it with its own
+// mutexes and it may be shared between threads.
+unsafe impl Send for EnvironmentHandle {}
+unsafe impl Sync for EnvironmentHandle {}
+
+/// Owned `OCIEnv` handle.
+///
+/// Cloning is cheap and shares the same handle, which is freed once the last clone and every
+/// handle allocated from it are dropped.
+#[derive(Clone)]
+pub struct Environment {
+    inner: Arc<EnvironmentHandle>,
+}
+
+impl Environment {
+    /// Creates a new environment in `OCI_THREADED` mode
+    /// using [`oci_env_nls_create`](fn.oci_env_nls_create.html).
+    ///
+    /// The client character set is `AL32UTF8`, the national character set is taken from `NLS_NCHAR`.
+    pub fn new() -> Result<Environment, OracleError> {
+        Environment::builder().build()
+    }
+
+    /// Starts configuring a new environment.
+    pub fn builder() -> EnvironmentBuilder {
+        EnvironmentBuilder { mode: OCIMode::Threaded, charset: None, ncharset: None, allocator: None }
+    }
+
+    /// Raw `OCIEnv` pointer.
+    pub fn as_ptr(&self) -> *mut OCIEnv {
+        self.inner.handle
+    }
+
+    /// Id of the client character set, e.g. `873` for `AL32UTF8`.
+    pub fn charset_id(&self) -> u16 {
+        self.inner.charset
+    }
+
+    /// Id of the client national character set.
+    pub fn ncharset_id(&self) -> u16 {
+        self.inner.ncharset
+    }
+
+    /// Conversion of text between Rust strings and the client character set.
+    pub fn encoding(&self) -> Encoding {
+        self.inner.encoding
+    }
+
+    fn alloc(&self, htype: OCIHandleType) -> Result<*mut c_void, OracleError> {
+        oci_handle_alloc(self.as_ptr(), htype)
+    }
+}
+
+/// Configuration of a new [`Environment`](struct.Environment.html),
+/// e.g. `Environment::builder().charset("AL32UTF8").build()`.
+#[derive(Clone)]
+pub struct EnvironmentBuilder {
+    mode:      OCIMode,
+    charset:   Option<Charset>,
+    ncharset:  Option<Charset>,
+    allocator: Option<Arc<dyn OciAllocator>>,
+}
+
+impl EnvironmentBuilder {
+    /// Adds the flags of `mode`, `OCI_THREADED` is always set.
+    pub fn mode(mut self, mode: OCIMode) -> EnvironmentBuilder {
+        self.mode |= mode;
+        self
+    }
+
+    /// Sets the client character set, which all text is exchanged in, `AL32UTF8` by default.
+    ///
+    /// The character set of `NLS_LANG` is not used, as it may be one text cannot be converted for.
+    ///
+    /// Text is converted for `AL32UTF8`, `UTF8`, `WE8ISO8859P1` and `US7ASCII`,
+    /// other character sets are rejected by [`build`](#method.build).
+    pub fn charset<C: Into<Charset>>(mut self, charset: C) -> EnvironmentBuilder {
+        self.charset = Some(charset.into());
+        self
+    }
+
+    /// Sets the client national character set, e.g. `AL16UTF16`, instead of taking it
+    /// from `NLS_NCHAR`.
+    pub fn ncharset<C: Into<Charset>>(mut self, ncharset: C) -> EnvironmentBuilder {
+        self.ncharset = Some(ncharset.into());
+        self
+    }
+
+    /// Makes OCI allocate all memory of the environment with `allocator`.
+    ///
+    /// The allocator is kept until the environment handle is freed.
+    pub fn allocator<A: OciAllocator + 'static>(mut self, allocator: A) -> EnvironmentBuilder {
+        self.allocator = Some(Arc::new(allocator));
+        self
+    }
+
+    /// Creates the environment with [`oci_env_nls_create`](fn.oci_env_nls_create.html).
+    ///
+    /// Character set names are resolved in a temporary environment first.
+    pub fn build(self) -> Result<Environment, OracleError> {
+        // Temporary environment to resolve character set names in.
+        let mut names = None;
+        let charset = match resolve(self.charset, &mut names)? {
+            0  => charset::AL32UTF8,
+            id => id,
+        };
+        let ncharset = resolve(self.ncharset, &mut names)?;
+        drop(names);
+        let encoding = Encoding::of(charset)?;
+        let mut env = EnvironmentHandle::create(self.mode, charset, ncharset, self.allocator)?;
+        if ncharset == 0 {
+            env.ncharset = env.charset_attr(OCIAttribute::EnvNcharsetId)?;
+        }
+        env.encoding = encoding;
+        Ok(Environment { inner: Arc::new(env) })
+    }
+}
+
+impl fmt::Debug for EnvironmentBuilder {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("EnvironmentBuilder")
+            .field("mode", &self.mode)
+            .field("charset", &self.charset)
+            .field("ncharset", &self.ncharset)
+            .field("allocator", &self.allocator.is_some())
+            .finish()
+    }
+}
+
+/// Id of `charset`, 0 if it is not set.
+fn resolve(charset: Option<Charset>, names: &mut Option<EnvironmentHandle>) -> Result<u16, OracleError> {
+    match charset {
+        None                      => Ok(0),
+        Some(Charset::Id(id))     => Ok(id),
+        Some(Charset::Name(name)) => {
+            let env = match *names {
+                Some(ref env) => env.handle,
+                None          => names.insert(EnvironmentHandle::create(OCIMode::Default, 0, 0, None)?).handle,
+            };
+            oci_nls_charset_name_to_id(env, &name)
+        },
+    }
+}
+
+/// Owned `OCIError` handle.
+pub struct ErrorHandle {
+    handle:   *mut OCIError,
+    warnings: Mutex<Warnings>,
+    env:      Environment,
+}
+
+impl ErrorHandle {
+    /// Allocates a new error handle in `env`.
+    pub fn new(env: &Environment) -> Result<ErrorHandle, OracleError> {
+        let handle = env.alloc(OCIHandleType::Error)?;
+        Ok(ErrorHandle {
+            handle:   handle as *mut OCIError,
+            warnings: Mutex::new(Warnings { kept: Vec::new(), handler: None }),
+            env:      env.clone(),
+        })
+    }
+
+    /// Raw `OCIError` pointer. Messages of the errors the next call on this thread reports
+    /// through it are decoded with the encoding of its environment.
+    pub fn as_ptr(&self) -> *mut OCIError {
+        set_error_encoding(self.env.encoding());
+        self.handle
+    }
+
+    /// Reads the error stored in the handle with [`oci_error_get`](fn.oci_error_get.html).
+    pub fn get(&self, location: &str) -> OracleError {
+        oci_error_get(self.as_ptr(), location)
+    }
+
+    /// Makes an OCI call with the raw handle and keeps the warning it returns with
+    /// `OCI_SUCCESS_WITH_INFO`, see [`take_warnings`](#method.take_warnings).
+    pub fn call<T, F>(&self, call: F) -> T
+        where F: FnOnce(*mut OCIError) -> T {
+        let (result, warning) = self.call_with_warning(call);
+        if let Some(warning) = warning {
+            self.record_warning(warning);
+        }
+        result
+    }
+
+    /// Makes an OCI call with the raw handle and returns the warning it returns with
+    /// `OCI_SUCCESS_WITH_INFO` rather than keeping it.
+    pub fn call_with_warning<T, F>(&self, call: F) -> (T, Option<OracleError>)
+        where F: FnOnce(*mut OCIError) -> T {
+        take_warning();
+        let result = call(self.as_ptr());
+        (result, take_warning())
+    }
+
+    /// Keeps `warning` or passes it to the warning handler.
+    pub fn record_warning(&self, warning: OracleError) {
+        let mut warnings = self.warnings.lock().unwrap_or_else(|err| err.into_inner());
+        match warnings.handler.clone() {
+            Some(handler) => {
+                // The handler may make calls with this handle itself.
+                drop(warnings);
+                handler(&warning);
+            },
+            None => {
+                if warnings.kept.len() == MAX_WARNINGS {
+                    warnings.kept.remove(0);
+                }
+                warnings.kept.push(warning);
+            },
+        }
+    }
+
+    /// Removes and returns the warnings of the calls made with [`call`](#method.call), oldest
+    /// first.
+    ///
+    /// Only the last 64 warnings are kept.
+    pub fn take_warnings(&self) -> Vec<OracleError> {
+        let mut warnings = self.warnings.lock().unwrap_or_else(|err| err.into_inner());
+        mem::take(&mut warnings.kept)
+    }
+
+    /// Passes every later warning of the handle to `handler` instead of keeping it for
+    /// [`take_warnings`](#method.take_warnings).
+    pub fn set_warning_handler<F>(&self, handler: F)
+        where F: Fn(&OracleError) + Send + Sync + 'static {
+        self.warnings.lock().unwrap_or_else(|err| err.into_inner()).handler = Some(Arc::new(handler));
+    }
+
+    /// Keeps later warnings for [`take_warnings`](#method.take_warnings) again.
+    pub fn clear_warning_handler(&self) {
+        self.warnings.lock().unwrap_or_else(|err| err.into_inner()).handler = None;
+    }
+}
+
+impl Drop for ErrorHandle {
+    fn drop(&mut self) {
+        let _ = oci_handle_free(self.handle as *mut c_void, OCIHandleType::Error);
+    }
+}
+
+/// Number of warnings kept per error handle until they are taken.
+const MAX_WARNINGS: usize = 64;
+
+type WarningHandler = Arc<dyn Fn(&OracleError) + Send + Sync>;
+
+/// Warnings and warning handler of an error handle.
+struct Warnings {
+    kept:    Vec<OracleError>,
+    handler: Option<WarningHandler>,
+}
+
+thread_local! {
+    // Warning of the last call on this thread, which `check_error` only has the raw error handle
+    // of. `ErrorHandle::call` moves it to the handle the call was made with.
+    static LAST_WARNING: RefCell<Option<OracleError>> = const { RefCell::new(None) };
+}
+
+/// Keeps `warning` as the warning of the last call on this thread.
+pub fn set_warning(warning: OracleError) {
+    LAST_WARNING.with(|last| *last.borrow_mut() = Some(warning));
+}
+
+fn take_warning() -> Option<OracleError> {
+    LAST_WARNING.with(|last| last.borrow_mut().take())
+}
+
+thread_local! {
+    // Encoding of the environment of the error handle last handed out on this thread, which
+    // `oci_error_get` decodes messages with as it only has the raw handle.
+    static ERROR_ENCODING: Cell<Encoding> = const { Cell::new(Encoding::Utf8) };
+}
+
+fn set_error_encoding(encoding: Encoding) {
+    ERROR_ENCODING.with(|current| current.set(encoding));
+}
+
+/// Encoding of the messages of errors reported on this thread, UTF-8 unless an
+/// [`ErrorHandle`](struct.ErrorHandle.html) of another environment was used.
+pub fn error_encoding() -> Encoding {
+    ERROR_ENCODING.with(|current| current.get())
+}
+
+unsafe impl Send for ErrorHandle {}
+
+/// Owned `OCIServer` handle.
+pub struct Server {
+    handle: *mut OCIServer,
+    #[allow(dead_code)]
+    env:    Environment,
+}
+
+impl Server {
+    /// Allocates a new server handle in `env`.
+    pub fn new(env: &Environment) -> Result<Server, OracleError> {
+        let handle = env.alloc(OCIHandleType::Server)?;
+        Ok(Server { handle: handle as *mut OCIServer, env: env.clone() })
+    }
+
+    /// Raw `OCIServer` pointer.
+    pub fn as_ptr(&self) -> *mut OCIServer {
+        self.handle
+    }
+
+    /// Attaches to the database `db` with [`oci_server_attach`](fn.oci_server_attach.html).
+    pub fn attach(&self, error: &ErrorHandle, db: &str, mode: OCIMode) -> Result<(), OracleError> {
+        oci_server_attach(self.handle, error.as_ptr(), db.to_string(), mode)
+    }
+
+    /// Detaches from the database with [`oci_server_detach`](fn.oci_server_detach.html).
+    pub fn detach(&self, error: &ErrorHandle) -> Result<(), OracleError> {
+        oci_server_detach(self.handle, error.as_ptr())
+    }
+}
+
+impl Drop for Server {
+    fn drop(&mut self) {
+        let _ = oci_handle_free(self.handle as *mut c_void, OCIHandleType::Server);
+    }
+}
+
+unsafe impl Send for Server {}
+
+/// Owned `OCISvcCtx` handle.
+pub struct ServiceContext {
+    handle: *mut OCISvcCtx,
+    #[allow(dead_code)]
+    env:    Environment,
+    owned:  bool,
+}
+
+impl ServiceContext {
+    /// Allocates a new service context handle in `env`.
+    pub fn new(env: &Environment) -> Result<ServiceContext, OracleError> {
+        let handle = env.alloc(OCIHandleType::Service)?;
+        Ok(ServiceContext { handle: handle as *mut OCISvcCtx, env: env.clone(), owned: true })
+    }
+
+    /// Wraps a service context handed out by a pool with [`oci_session_get`](fn.oci_session_get.html).
+    ///
+    /// The handle belongs to the pool and is not freed on drop.
+    pub fn from_pool(env: &Environment, handle: *mut OCISvcCtx) -> ServiceContext {
+        ServiceContext { handle, env: env.clone(), owned: false }
+    }
+
+    /// Raw `OCISvcCtx` pointer.
+    pub fn as_ptr(&self) -> *mut OCISvcCtx {
+        self.handle
+    }
+
+    /// Sets the `OCI_ATTR_SERVER` attribute of the service context.
+    pub fn set_server(&self, server: &Server, error: &ErrorHandle) -> Result<(), OracleError> {
+        oci_attr_set(self.handle as *mut c_void,
+                     OCIHandleType::Service,
+                     server.as_ptr() as *mut c_void,
+                     OCIAttribute::Server,
+                     error.as_ptr())
+    }
+
+    /// Sets the `OCI_ATTR_SESSION` attribute of the service context.
+    pub fn set_session(&self, session: &Session, error: &ErrorHandle) -> Result<(), OracleError> {
+        oci_attr_set(self.handle as *mut c_void,
+                     OCIHandleType::Service,
+                     session.as_ptr() as *mut c_void,
+                     OCIAttribute::Session,
+                     error.as_ptr())
+    }
+}
+
+impl Drop for ServiceContext {
+    fn drop(&mut self) {
+        if self.owned {
+            let _ = oci_handle_free(self.handle as *mut c_void, OCIHandleType::Service);
+        }
+    }
+}
+
+unsafe impl Send for ServiceContext {}
+
+/// Owned `OCISession` handle.
+pub struct Session {
+    handle: *mut OCISession,
+    env:    Environment,
+}
+
+impl Session {
+    /// Allocates a new session handle in `env`.
+    pub fn new(env: &Environment) -> Result<Session, OracleError> {
+        let handle = env.alloc(OCIHandleType::Session)?;
+        Ok(Session { handle: handle as *mut OCISession, env: env.clone() })
+    }
+
+    /// Raw `OCISession` pointer.
+    pub fn as_ptr(&self) -> *mut OCISession {
+        self.handle
+    }
+
+    /// Sets the `OCI_ATTR_USERNAME` attribute of the session.
+    pub fn set_username(&self, username: &str, error: &ErrorHandle) -> Result<(), OracleError> {
+        self.set_text(username, OCIAttribute::Username, error)
+    }
+
+    /// Sets the `OCI_ATTR_PASSWORD` attribute of the session.
+    pub fn set_password(&self, password: &str, error: &ErrorHandle) -> Result<(), OracleError> {
+        self.set_text(password, OCIAttribute::Password, error)
+    }
+
+    /// Begins the session with [`oci_session_begin`](fn.oci_session_begin.html).
+    pub fn begin(&self,
+                 service: &ServiceContext,
+                 error: &ErrorHandle,
+                 credentials_type: OCICredentialsType,
+                 mode: OCIAuthMode) -> Result<(), OracleError> {
+        error.call(|error| oci_session_begin(service.as_ptr(), error, self.handle, credentials_type, mode))
+    }
+
+    /// Ends the session with [`oci_session_end`](fn.oci_session_end.html).
+    pub fn end(&self, service: &ServiceContext, error: &ErrorHandle) -> Result<(), OracleError> {
+        oci_session_end(service.as_ptr(), error.as_ptr(), self.handle)
+    }
+
+    fn set_text(&self,
+                value: &str,
+                attr_type: OCIAttribute,
+                error: &ErrorHandle) -> Result<(), OracleError> {
+        let value = match CString::new(self.env.encoding().encode(value)?) {
+            Ok(value) => value,
+            Err(_)    => return Err(OracleError::new("Value contains a nul byte", "Session::set_text")),
+        };
+        oci_attr_set(self.handle as *mut c_void,
+                     OCIHandleType::Session,
+                     value.as_ptr() as *mut c_void,
+                     attr_type,
+                     error.as_ptr())
+    }
+}
+
+impl Drop for Session {
+    fn drop(&mut self) {
+        let _ = oci_handle_free(self.handle as *mut c_void, OCIHandleType::Session);
+    }
+}
+
+unsafe impl Send for Session {}
+
+/// Owned OCI descriptor, freed with `OCIDescriptorFree()` on drop.
+pub struct Descriptor {
+    ptr:   *mut c_void,
+    dtype: OCIDescriptorType,
+    #[allow(dead_code)]
+    env:   Environment,
+}
+
+impl Descriptor {
+    /// Allocates a new descriptor of `dtype` in `env`.
+    pub fn new(env: &Environment, dtype: OCIDescriptorType) -> Result<Descriptor, OracleError> {
+        let ptr = oci_descriptor_alloc(env.as_ptr(), dtype)?;
+        Ok(Descriptor { ptr, dtype, env: env.clone() })
+    }
+
+    /// Raw descriptor pointer.
+    pub fn as_ptr(&self) -> *mut c_void {
+        self.ptr
+    }
+
+    /// Location of the descriptor pointer, passed as the value of binds and defines.
+    pub fn ptr_mut(&mut self) -> *mut *mut c_void {
+        &mut self.ptr
+    }
+}
+
+impl Drop for Descriptor {
+    fn drop(&mut self) {
+        let _ = oci_descriptor_free(self.ptr, self.dtype);
+    }
+}
+
+unsafe impl Send for Descriptor {}
+
+// Shared copies of a descriptor are only passed to OCI calls, which the threaded environment
+// serialises.
+unsafe impl Sync for Descriptor {}
+
+impl fmt::Debug for Descriptor {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Descriptor({:p})", self.ptr)
+    }
+}
+
+/// Descriptors are equal if they are the same descriptor.
+impl PartialEq for Descriptor {
+    fn eq(&self, other: &Descriptor) -> bool {
+        self.ptr == other.ptr
+    }
+}
@@ -0,0 +1,314 @@
+//! Oracle `INTERVAL YEAR TO MONTH` and `INTERVAL DAY TO SECOND` values
+
+use std::time::Duration;
+use {OCIEnv, OCIError, OCIInterval, OCIDataType, OracleError};
+use {oci_interval_set_year_month, oci_interval_get_year_month, oci_interval_set_day_second,
+     oci_interval_get_day_second};
+use value::{SqlValue, ToSql, FromSql, check_not_null, conversion_error};
+
+/// Largest number of years or days an interval can hold.
+const MAX_LEADING: i32 = 999_999_999;
+
+/// Bias added to the 4-byte fields of the internal format.
+const FIELD_BIAS: u32 = 0x8000_0000;
+
+/// Bias added to the 1-byte fields of the internal format.
+const UNIT_BIAS: i32 = 60;
+
+const SECONDS_PER_DAY: u64 = 86_400;
+
+/// Oracle `INTERVAL YEAR TO MONTH`.
+///
+/// Carried in a [`SqlValue`](struct.SqlValue.html) in the 5-byte internal format: the years
+/// biased by 2^31 as a big-endian 4-byte number and the months biased by 60.
+/// Both fields have the sign of the interval.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct IntervalYM {
+    /// Years.
+    pub years:  i32,
+    /// Months, -11 to 11.
+    pub months: i32,
+}
+
+impl IntervalYM {
+    /// Creates an interval of `years` and `months`.
+    pub fn new(years: i32, months: i32) -> IntervalYM {
+        IntervalYM { years, months }
+    }
+
+    fn decode(bytes: &[u8]) -> Result<IntervalYM, OracleError> {
+        if bytes.len() != 5 {
+            return Err(OracleError::new("Invalid INTERVAL YEAR TO MONTH length", "IntervalYM::decode"));
+        }
+        Ok(IntervalYM {
+            years:  read_field(&bytes[..4]),
+            months: bytes[4] as i32 - UNIT_BIAS,
+        })
+    }
+
+    fn encode(&self) -> Result<Vec<u8>, OracleError> {
+        if self.years.unsigned_abs() > MAX_LEADING as u32 || self.months.unsigned_abs() > 11
+           || (self.years < 0 && self.months > 0) || (self.years > 0 && self.months < 0) {
+            return Err(OracleError::new(&format!("{:?} is out of range of Oracle INTERVAL YEAR TO MONTH", self),
+                                        "IntervalYM::encode"));
+        }
+        let mut bytes = write_field(self.years).to_vec();
+        bytes.push((self.months + UNIT_BIAS) as u8);
+        Ok(bytes)
+    }
+}
+
+/// Oracle `INTERVAL DAY TO SECOND`.
+///
+/// Carried in a [`SqlValue`](struct.SqlValue.html) in the 11-byte internal format: the days
+/// biased by 2^31 as a big-endian 4-byte number, the hours, minutes and seconds biased by 60
+/// and the nanoseconds biased by 2^31 as a big-endian 4-byte number.
+/// All fields have the sign of the interval.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct IntervalDS {
+    /// Days.
+    pub days:        i32,
+    /// Hours, -23 to 23.
+    pub hours:       i32,
+    /// Minutes, -59 to 59.
+    pub minutes:     i32,
+    /// Seconds, -59 to 59.
+    pub seconds:     i32,
+    /// Fractional second in nanoseconds.
+    pub nanoseconds: i32,
+}
+
+impl IntervalDS {
+    /// Creates an interval of the given days, hours, minutes, seconds and nanoseconds.
+    pub fn new(days: i32, hours: i32, minutes: i32, seconds: i32, nanoseconds: i32) -> IntervalDS {
+        IntervalDS { days, hours, minutes, seconds, nanoseconds }
+    }
+
+    /// Interval of the length of `duration`, negative if `negative` is set.
+    pub fn from_duration(duration: Duration, negative: bool) -> Result<IntervalDS, OracleError> {
+        let secs = duration.as_secs();
+        if secs / SECONDS_PER_DAY > MAX_LEADING as u64 {
+            return Err(OracleError::new(&format!("{:?} is out of range of Oracle INTERVAL DAY TO SECOND",
+                                                 duration),
+                                        "IntervalDS::from_duration"));
+        }
+        let sign = if negative { -1 } else { 1 };
+        Ok(IntervalDS {
+            days:        sign * (secs / SECONDS_PER_DAY) as i32,
+            hours:       sign * (secs % SECONDS_PER_DAY / 3600) as i32,
+            minutes:     sign * (secs % 3600 / 60) as i32,
+            seconds:     sign * (secs % 60) as i32,
+            nanoseconds: sign * duration.subsec_nanos() as i32,
+        })
+    }
+
+    /// Length of the interval and whether it is negative.
+    pub fn to_duration(&self) -> (Duration, bool) {
+        let secs = self.days.unsigned_abs() as u64 * SECONDS_PER_DAY
+                   + self.hours.unsigned_abs() as u64 * 3600
+                   + self.minutes.unsigned_abs() as u64 * 60
+                   + self.seconds.unsigned_abs() as u64;
+        (Duration::new(secs, self.nanoseconds.unsigned_abs()), self.is_negative())
+    }
+
+    /// Whether the interval is negative.
+    pub fn is_negative(&self) -> bool {
+        self.days < 0 || self.hours < 0 || self.minutes < 0 || self.seconds < 0 || self.nanoseconds < 0
+    }
+
+    fn decode(bytes: &[u8]) -> Result<IntervalDS, OracleError> {
+        if bytes.len() != 11 {
+            return Err(OracleError::new("Invalid INTERVAL DAY TO SECOND length", "IntervalDS::decode"));
+        }
+        Ok(IntervalDS {
+            days:        read_field(&bytes[..4]),
+            hours:       bytes[4] as i32 - UNIT_BIAS,
+            minutes:     bytes[5] as i32 - UNIT_BIAS,
+            seconds:     bytes[6] as i32 - UNIT_BIAS,
+            nanoseconds: read_field(&bytes[7..]),
+        })
+    }
+
+    fn encode(&self) -> Result<Vec<u8>, OracleError> {
+        let fields = [self.days, self.hours, self.minutes, self.seconds, self.nanoseconds];
+        if self.days.unsigned_abs() > MAX_LEADING as u32 || self.hours.unsigned_abs() > 23
+           || self.minutes.unsigned_abs() > 59 || self.seconds.unsigned_abs() > 59
+           || self.nanoseconds.unsigned_abs() > 999_999_999
+           || (fields.iter().any(|&f| f < 0) && fields.iter().any(|&f| f > 0)) {
+            return Err(OracleError::new(&format!("{:?} is out of range of Oracle INTERVAL DAY TO SECOND", self),
+                                        "IntervalDS::encode"));
+        }
+        let mut bytes = write_field(self.days).to_vec();
+        bytes.push((self.hours + UNIT_BIAS) as u8);
+        bytes.push((self.minutes + UNIT_BIAS) as u8);
+        bytes.push((self.seconds + UNIT_BIAS) as u8);
+        bytes.extend_from_slice(&write_field(self.nanoseconds));
+        Ok(bytes)
+    }
+}
+
+fn read_field(bytes: &[u8]) -> i32 {
+    u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]).wrapping_sub(FIELD_BIAS) as i32
+}
+
+fn write_field(value: i32) -> [u8; 4] {
+    (value as u32).wrapping_add(FIELD_BIAS).to_be_bytes()
+}
+
+/// Bound as `SQLT_INTERVAL_YM`.
+impl ToSql for IntervalYM {
+    fn to_sql(&self) -> Result<SqlValue, OracleError> {
+        Ok(SqlValue::new(OCIDataType::IntervalYearToMonth, self.encode()?))
+    }
+}
+
+impl FromSql for IntervalYM {
+    fn from_sql(value: &SqlValue) -> Result<IntervalYM, OracleError> {
+        check_not_null(value, "IntervalYM")?;
+        match value.data_type() {
+            OCIDataType::IntervalYearToMonth => IntervalYM::decode(value.as_bytes()),
+            _ => Err(conversion_error(value, "IntervalYM")),
+        }
+    }
+}
+
+/// Bound as `SQLT_INTERVAL_DS`.
+impl ToSql for IntervalDS {
+    fn to_sql(&self) -> Result<SqlValue, OracleError> {
+        Ok(SqlValue::new(OCIDataType::IntervalDayToSecond, self.encode()?))
+    }
+}
+
+impl FromSql for IntervalDS {
+    fn from_sql(value: &SqlValue) -> Result<IntervalDS, OracleError> {
+        check_not_null(value, "IntervalDS")?;
+        match value.data_type() {
+            OCIDataType::IntervalDayToSecond => IntervalDS::decode(value.as_bytes()),
+            _ => Err(conversion_error(value, "IntervalDS")),
+        }
+    }
+}
+
+/// Bound as a positive `INTERVAL DAY TO SECOND`.
+impl ToSql for Duration {
+    fn to_sql(&self) -> Result<SqlValue, OracleError> {
+        IntervalDS::from_duration(*self, false)?.to_sql()
+    }
+}
+
+/// Negative intervals are rejected, use [`IntervalDS`](struct.IntervalDS.html) to read them.
+impl FromSql for Duration {
+    fn from_sql(value: &SqlValue) -> Result<Duration, OracleError> {
+        match IntervalDS::from_sql(value)?.to_duration() {
+            (duration, false) => Ok(duration),
+            (duration, true)  => Err(OracleError::new(&format!("Cannot convert -{:?} to Duration", duration),
+                                                      "FromSql::from_sql")),
+        }
+    }
+}
+
+/// Reads a fetched `OCIInterval` descriptor into a value of `data_type`.
+pub fn read_interval(envh: *mut OCIEnv,
+                     error_handle: *mut OCIError,
+                     interval: *mut OCIInterval,
+                     data_type: OCIDataType) -> Result<SqlValue, OracleError> {
+    let bytes = if data_type == OCIDataType::IntervalYearToMonth {
+        let (years, months) = oci_interval_get_year_month(envh, error_handle, interval)?;
+        IntervalYM::new(years, months).encode()?
+    } else {
+        let (days, hours, minutes, seconds, nanoseconds) =
+            oci_interval_get_day_second(envh, error_handle, interval)?;
+        IntervalDS::new(days, hours, minutes, seconds, nanoseconds).encode()?
+    };
+    Ok(SqlValue::new(data_type, bytes))
+}
+
+/// Fills an `OCIInterval` descriptor from an interval `value`.
+pub fn construct_interval(envh: *mut OCIEnv,
+                          error_handle: *mut OCIError,
+                          interval: *mut OCIInterval,
+                          value: &SqlValue) -> Result<(), OracleError> {
+    if value.data_type() == OCIDataType::IntervalYearToMonth {
+        let ym = IntervalYM::from_sql(value)?;
+        oci_interval_set_year_month(envh, error_handle, interval, ym.years, ym.months)
+    } else {
+        let ds = IntervalDS::from_sql(value)?;
+        oci_interval_set_day_second(envh, error_handle, interval,
+                                    (ds.days, ds.hours, ds.minutes, ds.seconds, ds.nanoseconds))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+    use value::{FromSql, ToSql};
+    use super::{IntervalYM, IntervalDS, MAX_LEADING};
+
+    #[test]
+    fn encodes_year_to_month() {
+        assert_eq!(IntervalYM::new(0, 0).encode().unwrap(), [0x80, 0, 0, 0, 60]);
+        assert_eq!(IntervalYM::new(1, 2).encode().unwrap(), [0x80, 0, 0, 1, 62]);
+        assert_eq!(IntervalYM::new(-1, -2).encode().unwrap(), [0x7F, 0xFF, 0xFF, 0xFF, 58]);
+        for &(years, months) in &[(0, 0), (5, 11), (-3, -11), (MAX_LEADING, 0), (-MAX_LEADING, -1)] {
+            let interval = IntervalYM::new(years, months);
+            assert_eq!(IntervalYM::decode(&interval.encode().unwrap()).unwrap(), interval);
+        }
+    }
+
+    #[test]
+    fn rejects_invalid_year_to_month() {
+        for &(years, months) in &[(1, -1), (-1, 1), (0, 12), (0, -12), (MAX_LEADING + 1, 0),
+                                  (i32::MIN, 0), (0, i32::MIN)] {
+            assert!(IntervalYM::new(years, months).to_sql().is_err(), "{} {}", years, months);
+        }
+        assert!(IntervalYM::decode(&[0x80, 0, 0, 0]).is_err());
+    }
+
+    #[test]
+    fn encodes_day_to_second() {
+        assert_eq!(IntervalDS::new(1, 2, 3, 4, 5).encode().unwrap(),
+                   [0x80, 0, 0, 1, 62, 63, 64, 0x80, 0, 0, 5]);
+        for interval in &[IntervalDS::new(0, 0, 0, 0, 0),
+                          IntervalDS::new(MAX_LEADING, 23, 59, 59, 999_999_999),
+                          IntervalDS::new(-2, -23, 0, -1, -500)] {
+            assert_eq!(IntervalDS::decode(&interval.encode().unwrap()).unwrap(), *interval);
+        }
+    }
+
+    #[test]
+    fn rejects_invalid_day_to_second() {
+        for interval in &[IntervalDS::new(1, -1, 0, 0, 0),
+                          IntervalDS::new(0, 0, 0, -1, 1),
+                          IntervalDS::new(0, 24, 0, 0, 0),
+                          IntervalDS::new(0, 0, 60, 0, 0),
+                          IntervalDS::new(0, 0, 0, 60, 0),
+                          IntervalDS::new(0, 0, 0, 0, 1_000_000_000),
+                          IntervalDS::new(i32::MIN, 0, 0, 0, 0),
+                          IntervalDS::new(0, i32::MIN, 0, 0, 0),
+                          IntervalDS::new(0, 0, 0, 0, i32::MIN)] {
+            assert!(interval.to_sql().is_err(), "{:?}", interval);
+        }
+        assert!(IntervalDS::decode(&[0; 10]).is_err());
+    }
+
+    #[test]
+    fn converts_durations() {
+        let duration = Duration::new(2 * 86_400 + 3 * 3600 + 4 * 60 + 5, 6);
+        let interval = IntervalDS::from_duration(duration, false).unwrap();
+        assert_eq!(interval, IntervalDS::new(2, 3, 4, 5, 6));
+        assert_eq!(interval.to_duration(), (duration, false));
+        let negative = IntervalDS::from_duration(duration, true).unwrap();
+        assert_eq!(negative, IntervalDS::new(-2, -3, -4, -5, -6));
+        assert_eq!(negative.to_duration(), (duration, true));
+        assert!(IntervalDS::from_duration(Duration::from_secs((MAX_LEADING as u64 + 1) * 86_400), false).is_err());
+    }
+
+    #[test]
+    fn converts_durations_through_values() {
+        let duration = Duration::new(90_061, 500);
+        assert_eq!(Duration::from_sql(&duration.to_sql().unwrap()).unwrap(), duration);
+        let negative = IntervalDS::new(0, 0, 0, -1, 0).to_sql().unwrap();
+        assert!(Duration::from_sql(&negative).is_err());
+        assert_eq!(IntervalDS::from_sql(&negative).unwrap(), IntervalDS::new(0, 0, 0, -1, 0));
+    }
+}
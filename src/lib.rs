@@ -1,12 +1,52 @@
 //! Call bindings to Oracle OCI
 
 extern crate libc;
+#[cfg(feature = "chrono")]
+extern crate chrono;
+#[cfg(feature = "time")]
+extern crate time;
 
-pub use libc::{c_void, c_ushort, c_ulong, c_uchar, c_char, c_uint, c_int};
-use std::ffi::CStr;
+pub use libc::{c_void, c_ushort, c_ulong, c_uchar, c_char, c_uint, c_int, c_short, c_schar, size_t};
+use std::ffi::{CStr, CString};
 use std::error;
 use std::fmt;
+use std::mem;
+use std::ops;
 use std::ptr;
+use std::slice;
+use charset::Encoding;
+
+mod handle;
+mod connection;
+mod statement;
+mod value;
+mod number;
+mod datetime;
+mod interval;
+mod lob;
+mod transaction;
+mod charset;
+mod allocator;
+mod session_pool;
+mod connection_pool;
+mod pool;
+mod statement_cache;
+
+pub use handle::{Environment, EnvironmentBuilder, ErrorHandle, Server, ServiceContext, Session};
+pub use connection::Connection;
+pub use statement::{Statement, StatementType, ColumnInfo, BindIndex, BatchError, Rows, Row};
+pub use value::{SqlValue, ToSql, FromSql};
+pub use number::OracleNumber;
+pub use datetime::{OracleDate, OracleTimestamp};
+pub use interval::{IntervalYM, IntervalDS};
+pub use lob::Lob;
+pub use transaction::Transaction;
+pub use charset::Charset;
+pub use allocator::OciAllocator;
+pub use session_pool::{SessionPool, SessionPoolBuilder, PoolGetMode};
+pub use connection_pool::{ConnectionPool, ConnectionPoolBuilder, PooledServer};
+pub use pool::{Pool, PoolBuilder, PooledConnection, PoolMetrics, ConnectionFactory, OracleConnectionFactory};
+pub use statement_cache::StatementCache;
 
 /// Opaque pointer to OCIEnv
 #[repr(C)]
@@ -20,6 +60,14 @@ pub struct OCIError;
 #[repr(C)]
 pub struct OCISvcCtx;
 
+/// Opaque pointer to OCISPool
+#[repr(C)]
+pub struct OCISPool;
+
+/// Opaque pointer to OCICPool
+#[repr(C)]
+pub struct OCICPool;
+
 /// Opaque pointer to OCIServer
 #[repr(C)]
 pub struct OCIServer;
@@ -36,70 +84,418 @@ pub struct OCIStmt;
 #[repr(C)]
 struct OCISnapshot;
 
-/// OCI Mode type.
+/// Opaque pointer to OCIDefine
+#[repr(C)]
+pub struct OCIDefine;
+
+/// Opaque pointer to OCIBind
+#[repr(C)]
+pub struct OCIBind;
+
+/// Opaque pointer to OCIDateTime
+#[repr(C)]
+pub struct OCIDateTime;
+
+/// Opaque pointer to OCIInterval
+#[repr(C)]
+pub struct OCIInterval;
+
+/// Opaque pointer to OCILobLocator
+#[repr(C)]
+pub struct OCILobLocator;
+
+/// Implements combining, testing and converting for a set of mode flags wrapping a `c_uint`.
+macro_rules! mode_flags {
+    ($name:ident) => {
+        impl $name {
+            /// Flags as passed to OCI.
+            pub fn bits(&self) -> c_uint {
+                self.0
+            }
+
+            /// Whether every flag of `other` is set.
+            pub fn contains(&self, other: $name) -> bool {
+                self.0 & other.0 == other.0
+            }
+        }
+
+        impl Default for $name {
+            fn default() -> $name {
+                $name::Default
+            }
+        }
+
+        impl ops::BitOr for $name {
+            type Output = $name;
+
+            fn bitor(self, other: $name) -> $name {
+                $name(self.0 | other.0)
+            }
+        }
+
+        impl ops::BitOrAssign for $name {
+            fn bitor_assign(&mut self, other: $name) {
+                self.0 |= other.0
+            }
+        }
+
+        impl From<$name> for c_uint {
+            fn from(mode: $name) -> c_uint {
+                mode.0
+            }
+        }
+
+        impl fmt::Debug for $name {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(f, "{}({:#010x})", stringify!($name), self.0)
+            }
+        }
+    };
+}
+
+/// OCI Mode type, flags combined with `|`.
 /// Used in [`oci_env_nls_create`](fn.oci_env_nls_create.html),
-#[allow(dead_code)]
-pub enum OCIMode {
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct OCIMode(c_uint);
+
+#[allow(non_upper_case_globals)]
+impl OCIMode {
     /// `OCI_DEFAULT`. The default value, which is non-UTF-16 encoding.
-    Default = 0x00000000,
+    pub const Default: OCIMode = OCIMode(0x00000000);
 
     /// `OCI_THREADED`. Uses threaded environment.
     /// Internal data structures not exposed to the user are protected from concurrent
     /// accesses by multiple threads.
-    Threaded = 0x00000001,
+    pub const Threaded: OCIMode = OCIMode(0x00000001);
 
     /// `OCI_OBJECT`. Uses object features.
-    Object = 0x00000002,
+    pub const Object: OCIMode = OCIMode(0x00000002);
 
     /// `OCI_EVENTS`. Uses publish-subscribe notifications.
-    Events = 0x00000004,
+    pub const Events: OCIMode = OCIMode(0x00000004);
 
     /// `OCI_NO_UCB`. Suppresses the calling of the dynamic callback routine `OCIEnvCallback()`.
     /// The default behavior is to allow calling of `OCIEnvCallback()` when the environment
     /// is created.
-    NoUcb = 0x00000040,
+    pub const NoUcb: OCIMode = OCIMode(0x00000040);
 
     /// `OCI_NO_MUTEX`. No mutual exclusion (mutex) locking occurs in this mode.
     /// All OCI calls done on the environment handle, or on handles derived from the environment
     /// handle, must be serialized.
     /// `Threaded` must also be specified when `OCI_NO_MUTEX` is specified.
-    NoMutex = 0x00000080,
+    pub const NoMutex: OCIMode = OCIMode(0x00000080);
+
+    /// `OCI_CPOOL`. Only valid for [`oci_server_attach`](fn.oci_server_attach.html), which then
+    /// takes the name of a connection pool rather than a connect string.
+    pub const ConnectionPool: OCIMode = OCIMode(0x00000200);
 
     /// `OCI_SUPPRESS_NLS_VALIDATION`. Suppresses NLS character validation;
     /// NLS character validation suppression is on by default beginning with
     /// Oracle Database 11g Release 1 (11.1). Use `EnableNLSValidation` to
     /// enable NLS character validation.
-    SuppressNLSValidation = 0x00100000,
+    pub const SuppressNLSValidation: OCIMode = OCIMode(0x00100000);
 
     /// `OCI_NCHAR_LITERAL_REPLACE_ON`. Turns on N' substitution.
-    NcharLiteralReplaceOn = 0x00400000,
+    pub const NcharLiteralReplaceOn: OCIMode = OCIMode(0x00400000);
 
     /// `OCI_NCHAR_LITERAL_REPLACE_OFF`. Turns off N' substitution.
     /// If neither this mode nor `NcharLiteralReplaceOn` is used, the substitution is
     /// determined by the environment variable `ORA_NCHAR_LITERAL_REPLACE`, which can be set
     /// to `TRUE` or `FALSE`. When it is set to `TRUE`, the replacement is turned on; otherwise
     /// it is turned off, which is the default setting in OCI.
-    NcharLiteralReplaceOff = 0x00800000,
+    pub const NcharLiteralReplaceOff: OCIMode = OCIMode(0x00800000);
 
     /// `OCI_ENABLE_NLS_VALIDATION`. Enables NLS character validation.
-    EnableNLSValidation = 0x01000000,
+    pub const EnableNLSValidation: OCIMode = OCIMode(0x01000000);
+
+    /// Checks that the flags may be combined.
+    pub fn validate(&self) -> Result<(), OracleError> {
+        let conflict = if self.contains(OCIMode::NoMutex) && !self.contains(OCIMode::Threaded) {
+            "NoMutex requires Threaded"
+        } else if self.contains(OCIMode::NcharLiteralReplaceOn | OCIMode::NcharLiteralReplaceOff) {
+            "NcharLiteralReplaceOn and NcharLiteralReplaceOff exclude each other"
+        } else if self.contains(OCIMode::SuppressNLSValidation | OCIMode::EnableNLSValidation) {
+            "SuppressNLSValidation and EnableNLSValidation exclude each other"
+        } else {
+            return Ok(());
+        };
+        Err(OracleError::new(&format!("Invalid {:?}: {}", self, conflict), "OCIMode::validate"))
+    }
+}
+
+mode_flags!(OCIMode);
+
+/// Return code of an OCI function.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum OciStatus {
+    /// `OCI_SUCCESS`
+    Success,
+
+    /// `OCI_SUCCESS_WITH_INFO`. The call succeeded and diagnostics are in the error handle.
+    SuccessWithInfo,
+
+    /// `OCI_NO_DATA`. No more data, e.g. at the end of a fetch.
+    NoData,
+
+    /// `OCI_ERROR`. The call failed and the error is in the error handle.
+    Error,
+
+    /// `OCI_INVALID_HANDLE`. A handle passed to the call is invalid.
+    InvalidHandle,
+
+    /// `OCI_NEED_DATA`. The application must provide run-time data.
+    NeedData,
+
+    /// `OCI_STILL_EXECUTING`. A non-blocking call is still running.
+    StillExecuting,
+
+    /// `OCI_CONTINUE`. Returned by callbacks to let OCI continue the call.
+    Continue,
+
+    /// `OCI_ROWCBK_DONE`. Returned by row callbacks when they are done.
+    RowCallbackDone,
+
+    /// Code not documented by OCI.
+    Unknown(i32),
+}
+
+impl OciStatus {
+    /// Status of the OCI return code `code`.
+    pub fn from_code(code: c_int) -> OciStatus {
+        match code {
+            0      => OciStatus::Success,
+            1      => OciStatus::SuccessWithInfo,
+            100    => OciStatus::NoData,
+            -1     => OciStatus::Error,
+            -2     => OciStatus::InvalidHandle,
+            99     => OciStatus::NeedData,
+            -3123  => OciStatus::StillExecuting,
+            -24200 => OciStatus::Continue,
+            -24201 => OciStatus::RowCallbackDone,
+            code   => OciStatus::Unknown(code),
+        }
+    }
+
+    /// OCI return code of the status.
+    pub fn code(&self) -> c_int {
+        match *self {
+            OciStatus::Success         => 0,
+            OciStatus::SuccessWithInfo => 1,
+            OciStatus::NoData          => 100,
+            OciStatus::Error           => -1,
+            OciStatus::InvalidHandle   => -2,
+            OciStatus::NeedData        => 99,
+            OciStatus::StillExecuting  => -3123,
+            OciStatus::Continue        => -24200,
+            OciStatus::RowCallbackDone => -24201,
+            OciStatus::Unknown(code)   => code,
+        }
+    }
+}
+
+/// Single diagnostic record read with [`oci_error_get`](fn.oci_error_get.html).
+#[derive(Clone, Debug, PartialEq)]
+pub struct ErrorRecord {
+    code:     isize,
+    message:  String,
+    sqlstate: Option<String>,
+}
+
+impl ErrorRecord {
+    /// Oracle error code, e.g. `942` for `ORA-00942`.
+    pub fn code(&self) -> isize {
+        self.code
+    }
+
+    /// Message of the record.
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    /// SQLSTATE of the record, if the client reports one.
+    pub fn sqlstate(&self) -> Option<&str> {
+        self.sqlstate.as_deref()
+    }
 }
 
 /// Represent Oracle error.
 #[derive(Debug)]
 pub struct OracleError {
     /// Oracle error code.
-    code:     isize,
+    code:         isize,
     /// Message.
-    message:  String,
+    message:      String,
+    /// Function where the error occurred.
+    location:     String,
+    /// Return code the error was raised for.
+    kind:         OciStatus,
+    /// SQLSTATE of the first record.
+    sqlstate:     Option<String>,
+    /// All diagnostic records, the first one is also in `code` and `message`.
+    records:      Vec<ErrorRecord>,
+    /// Offset of a parse error in the statement text.
+    parse_offset: u16,
+}
+
+impl OracleError {
+    /// Error detected by the crate itself rather than reported by OCI.
+    fn new(message: &str, location: &str) -> OracleError {
+        OracleError::with_status(-1, message.to_string(), location, OciStatus::Error)
+    }
+
+    fn with_status(code: isize, message: String, location: &str, kind: OciStatus) -> OracleError {
+        OracleError {
+            code,
+            message,
+            location:     location.to_string(),
+            kind,
+            sqlstate:     None,
+            records:      Vec::new(),
+            parse_offset: 0,
+        }
+    }
+
+    /// Oracle error code of the first record, e.g. `942` for `ORA-00942`, or the OCI return code
+    /// if there are no records.
+    pub fn code(&self) -> isize {
+        self.code
+    }
+
+    /// Message of the first record.
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
     /// Function where the error occurred.
-    location: String,
+    pub fn location(&self) -> &str {
+        &self.location
+    }
+
+    /// Return code the error was raised for, `OciStatus::Error` for errors detected by the crate
+    /// and `OciStatus::SuccessWithInfo` for warnings.
+    pub fn kind(&self) -> OciStatus {
+        self.kind
+    }
+
+    /// SQLSTATE of the first record, if the client reports one.
+    pub fn sqlstate(&self) -> Option<&str> {
+        self.sqlstate.as_deref()
+    }
+
+    /// All diagnostic records of the error handle, empty if the error did not come from one.
+    pub fn records(&self) -> &[ErrorRecord] {
+        &self.records
+    }
+
+    /// Offset of a parse error in the statement text.
+    ///
+    /// It is 0 when the error is not a parse error, as well as for a parse error at the very start.
+    pub fn parse_offset(&self) -> u16 {
+        self.parse_offset
+    }
+
+    /// Whether the connection to the database is lost and has to be reestablished,
+    /// e.g. `ORA-03113: end-of-file on communication channel`.
+    pub fn is_connection_lost(&self) -> bool {
+        self.has_code(CONNECTION_LOST_CODES)
+    }
+
+    /// Whether the statement was rolled back as the victim of a deadlock (`ORA-00060`).
+    pub fn is_deadlock(&self) -> bool {
+        self.has_code(&[60])
+    }
+
+    /// Whether a unique constraint was violated (`ORA-00001`).
+    pub fn is_unique_violation(&self) -> bool {
+        self.has_code(&[1])
+    }
+
+    /// Whether a connect, a lock wait or the call itself timed out, e.g. `ORA-12170: TNS:Connect
+    /// timeout occurred`.
+    pub fn is_timeout(&self) -> bool {
+        self.has_code(TIMEOUT_CODES)
+    }
+
+    /// Whether the failed operation may succeed when retried, possibly on a new connection.
+    ///
+    /// This covers lost connections, deadlocks, timeouts and transient failures like a busy
+    /// listener or a serialization failure, but not errors in the statement or its data.
+    pub fn is_retryable(&self) -> bool {
+        self.is_connection_lost() || self.is_deadlock() || self.is_timeout()
+            || self.has_code(TRANSIENT_CODES)
+    }
+
+    /// Whether the error or any of its records has one of `codes`.
+    fn has_code(&self, codes: &[isize]) -> bool {
+        codes.contains(&self.code) || self.records.iter().any(|record| codes.contains(&record.code))
+    }
 }
 
+/// Codes of errors after which the connection is unusable.
+const CONNECTION_LOST_CODES: &[isize] = &[
+    28,    // your session has been killed
+    1012,  // not logged on
+    1033,  // ORACLE initialization or shutdown in progress
+    1034,  // ORACLE not available
+    1089,  // immediate shutdown in progress
+    1090,  // shutdown in progress
+    1092,  // ORACLE instance terminated
+    2396,  // exceeded maximum idle time
+    3113,  // end-of-file on communication channel
+    3114,  // not connected to ORACLE
+    3135,  // connection lost contact
+    12153, // TNS:not connected
+    12537, // TNS:connection closed
+    12547, // TNS:lost contact
+    12570, // TNS:packet reader failure
+    12571, // TNS:packet writer failure
+    12583, // TNS:no reader
+    25408, // can not safely replay call
+];
+
+/// Codes of errors raised when a connect, a lock wait or a call timed out.
+const TIMEOUT_CODES: &[isize] = &[
+    51,    // timeout occurred while waiting for a resource
+    2049,  // timeout: distributed transaction waiting for lock
+    3136,  // inbound connection timed out
+    3156,  // OCI call timed out
+    12170, // TNS:Connect timeout occurred
+    12535, // TNS:operation timed out
+    24457, // OCISessionGet() could not find a free session in the specified timeout period
+    30006, // resource busy; acquire with WAIT timeout expired
+];
+
+/// Codes of other errors that may go away when the operation is retried.
+const TRANSIENT_CODES: &[isize] = &[
+    54,    // resource busy and acquire with NOWAIT specified or timeout expired
+    4068,  // existing state of packages has been discarded
+    8177,  // can't serialize access for this transaction
+    12514, // TNS:listener does not currently know of service requested in connect descriptor
+    12516, // TNS:listener could not find available handler with matching protocol stack
+    12519, // TNS:no appropriate service handler found
+    12520, // TNS:listener could not find available handler for requested type of server
+    12521, // TNS:listener does not currently know of instance requested in connect descriptor
+    12528, // TNS:listener: all appropriate instances are blocking new connections
+    12541, // TNS:no listener
+    12543, // TNS:destination host unreachable
+    25402, // transaction must roll back
+    25403, // could not reconnect to server
+];
+
 impl fmt::Display for OracleError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!{f, "\n\n  Error code: {}\n  Error message: {}\n  Where: {}\n\n",
-               self.code, self.message, self.location}
+        write!{f, "\n\n  Error code: {}\n  Error message: {}\n  Where: {}\n",
+               self.code, self.message, self.location}?;
+        if let Some(ref sqlstate) = self.sqlstate {
+            writeln!(f, "  SQLSTATE: {}", sqlstate)?;
+        }
+        if self.parse_offset > 0 {
+            writeln!(f, "  Parse error offset: {}", self.parse_offset)?;
+        }
+        for record in self.records.iter().skip(1) {
+            writeln!(f, "  Also: {} {}", record.code, record.message)?;
+        }
+        f.write_str("\n")
     }
 }
 
@@ -141,6 +537,12 @@ pub enum OCIHandleType {
 
     /// `OCI_HTYPE_TRANS`
     Transaction = 10,
+
+    /// `OCI_HTYPE_CPOOL`
+    ConnectionPool = 26,
+
+    /// `OCI_HTYPE_SPOOL`
+    SessionPool = 27,
 }
 
 /// Type of credentials
@@ -153,38 +555,138 @@ pub enum OCICredentialsType {
     External = 2,
 }
 
-/// Type of authentication mode
-#[allow(dead_code)]
-pub enum OCIAuthMode {
+/// Type of authentication mode, flags combined with `|`
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct OCIAuthMode(c_uint);
+
+#[allow(non_upper_case_globals)]
+impl OCIAuthMode {
     /// `OCI_DEFAULT`
-    Default    = 0x00000000,
+    pub const Default: OCIAuthMode    = OCIAuthMode(0x00000000);
 
     /// `OCI_MIGRATE`
-    Migrate    = 0x00000001,
+    pub const Migrate: OCIAuthMode    = OCIAuthMode(0x00000001);
 
     /// `OCI_SYSDBA`
-    Sysdba     = 0x00000002,
+    pub const Sysdba: OCIAuthMode     = OCIAuthMode(0x00000002);
 
     /// `OCI_SYSOPER`
-    Sysoper    = 0x00000004,
+    pub const Sysoper: OCIAuthMode    = OCIAuthMode(0x00000004);
 
-    /// `OCI_PRELIM_AUTH`
-    PrelimAuth = 0x00000008,
+    /// `OCI_PRELIM_AUTH`. Only valid together with `Sysdba` or `Sysoper`.
+    pub const PrelimAuth: OCIAuthMode = OCIAuthMode(0x00000008);
 
     /// `OCI_STMT_CACHE`
-    StmtCache  = 0x00000040,
+    pub const StmtCache: OCIAuthMode  = OCIAuthMode(0x00000040);
+
+    /// Checks that the flags may be combined.
+    pub fn validate(&self) -> Result<(), OracleError> {
+        let conflict = if self.contains(OCIAuthMode::Sysdba | OCIAuthMode::Sysoper) {
+            "Sysdba and Sysoper exclude each other"
+        } else if self.contains(OCIAuthMode::PrelimAuth)
+                  && !self.contains(OCIAuthMode::Sysdba) && !self.contains(OCIAuthMode::Sysoper) {
+            "PrelimAuth requires Sysdba or Sysoper"
+        } else {
+            return Ok(());
+        };
+        Err(OracleError::new(&format!("Invalid {:?}: {}", self, conflict), "OCIAuthMode::validate"))
+    }
 }
 
+mode_flags!(OCIAuthMode);
+
+/// Mode of [`oci_stmt_execute`](fn.oci_stmt_execute.html), flags combined with `|`
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct OCIExecuteMode(c_uint);
+
+#[allow(non_upper_case_globals)]
+impl OCIExecuteMode {
+    /// `OCI_DEFAULT`
+    pub const Default: OCIExecuteMode         = OCIExecuteMode(0x00000000);
+
+    /// `OCI_DESCRIBE_ONLY`. Describes the select-list without executing the query.
+    pub const DescribeOnly: OCIExecuteMode    = OCIExecuteMode(0x00000010);
+
+    /// `OCI_COMMIT_ON_SUCCESS`. Commits the transaction if the execution succeeds.
+    pub const CommitOnSuccess: OCIExecuteMode = OCIExecuteMode(0x00000020);
+
+    /// `OCI_BATCH_ERRORS`. Continues an array DML after failed rows and collects their errors.
+    pub const BatchErrors: OCIExecuteMode     = OCIExecuteMode(0x00000080);
+
+    /// `OCI_PARSE_ONLY`. Parses the statement without executing it.
+    pub const ParseOnly: OCIExecuteMode       = OCIExecuteMode(0x00000100);
+
+    /// Checks that the flags may be combined.
+    pub fn validate(&self) -> Result<(), OracleError> {
+        if self.contains(OCIExecuteMode::DescribeOnly | OCIExecuteMode::ParseOnly) {
+            return Err(OracleError::new(&format!("Invalid {:?}: DescribeOnly and ParseOnly exclude each other",
+                                                 self),
+                                        "OCIExecuteMode::validate"));
+        }
+        Ok(())
+    }
+}
+
+mode_flags!(OCIExecuteMode);
+
 /// Type of syntax
 enum OCISyntax {
     /// `OCI_NTV_SYNTAX`
     NtvSyntax = 1,
 }
 
-/// Type of OCIStmtPrepare2 mode
-enum OCIStmtPrepare2Mode {
-    /// `OCI_DEFAULT`
-    Default = 0x00000000,
+/// Mode of [`oci_session_pool_create`](fn.oci_session_pool_create.html)
+enum OCISessionPoolMode {
+    /// `OCI_SPC_HOMOGENEOUS`. All sessions are created for the user given to the pool.
+    Homogeneous = 0x00000002,
+}
+
+/// Mode of [`oci_session_get`](fn.oci_session_get.html)
+enum OCISessionGetMode {
+    /// `OCI_SESSGET_SPOOL`. The session is taken from a session pool.
+    SessionPool = 0x00000001,
+}
+
+/// Mode of [`oci_session_release`](fn.oci_session_release.html)
+#[derive(Clone, Copy)]
+pub enum OCISessionReleaseMode {
+    /// `OCI_DEFAULT`. The session goes back to the pool.
+    Default     = 0x00000000,
+
+    /// `OCI_SESSRLS_DROPSESS`. The session is closed instead, e.g. because it is unusable.
+    DropSession = 0x00000001,
+}
+
+/// Mode of [`oci_bind_by_pos`](fn.oci_bind_by_pos.html) and [`oci_bind_by_name`](fn.oci_bind_by_name.html)
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum OCIBindMode {
+    /// `OCI_DEFAULT`. The value is read from the bound buffer.
+    Default    = 0x00000000,
+
+    /// `OCI_DATA_AT_EXEC`. The value is provided by the callbacks registered with
+    /// [`oci_bind_dynamic`](fn.oci_bind_dynamic.html), e.g. for `RETURNING INTO`.
+    DataAtExec = 0x00000002,
+}
+
+/// Mode of [`oci_stmt_prepare2`](fn.oci_stmt_prepare2.html)
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum OCIStmtPrepare2Mode {
+    /// `OCI_DEFAULT`. The statement is taken from the statement cache or prepared from its text.
+    Default         = 0x00000000,
+
+    /// `OCI_PREP2_CACHE_SEARCHONLY`. The statement is only taken from the statement cache,
+    /// the call fails if it is not there.
+    CacheSearchOnly = 0x00000010,
+}
+
+/// Mode of [`oci_stmt_release`](fn.oci_stmt_release.html)
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum OCIStmtReleaseMode {
+    /// `OCI_DEFAULT`. The statement goes back to the statement cache, if caching is enabled.
+    Default     = 0x00000000,
+
+    /// `OCI_STRLS_CACHE_DELETE`. The statement is removed from the statement cache.
+    CacheDelete = 0x00000010,
 }
 
 /// Type if OCI Attribute
@@ -223,13 +725,195 @@ pub enum OCIAttribute {
     /// Specifies a password to use for authentication.
     /// Attribute Data Type: oratext * [oratext = c_uchar]
     Password = 23,
+
+    /// `OCI_ATTR_ROW_COUNT`
+    ///
+    /// Mode: READ
+    ///
+    /// Gets the number of rows processed so far by the statement, wraps above 4294967295.
+    /// Attribute Data Type: ub4 * [ub4 = c_uint]
+    RowCount = 9,
+
+    /// `OCI_ATTR_PARAM_COUNT`
+    ///
+    /// Mode: READ
+    ///
+    /// Gets the number of columns in the select-list for the statement.
+    /// Attribute Data Type: ub4 * [ub4 = c_uint]
+    ParamCount = 18,
+
+    /// `OCI_ATTR_STMT_TYPE`
+    ///
+    /// Mode: READ
+    ///
+    /// Gets the type of the prepared statement, e.g. `OCI_STMT_SELECT`.
+    /// Attribute Data Type: ub2 * [ub2 = c_ushort]
+    StmtType = 24,
+
+    /// `OCI_ATTR_NUM_DML_ERRORS`
+    ///
+    /// Mode: READ
+    ///
+    /// Gets the number of rows that failed in an array DML executed with `OCI_BATCH_ERRORS`.
+    /// Attribute Data Type: ub4 * [ub4 = c_uint]
+    NumDmlErrors = 73,
+
+    /// `OCI_ATTR_DML_ROW_OFFSET`
+    ///
+    /// Mode: READ
+    ///
+    /// Gets the zero-based row of an array DML a batch error handle belongs to.
+    /// Attribute Data Type: ub4 * [ub4 = c_uint]
+    DmlRowOffset = 74,
+
+    /// `OCI_ATTR_ROWS_RETURNED`
+    ///
+    /// Mode: READ
+    ///
+    /// Gets the number of rows returned into a `RETURNING INTO` bind by the current iteration,
+    /// read from the bind handle in the out-bind callback.
+    /// Attribute Data Type: ub4 * [ub4 = c_uint]
+    RowsReturned = 42,
+
+    /// `OCI_ATTR_PARSE_ERROR_OFFSET`
+    ///
+    /// Mode: READ
+    ///
+    /// Gets the offset in the statement text where a parse error occurred.
+    /// Attribute Data Type: ub2 * [ub2 = c_ushort]
+    ParseErrorOffset = 129,
+
+    /// `OCI_ATTR_CONN_NOWAIT`
+    ///
+    /// Mode: READ/WRITE
+    ///
+    /// When set, getting a connection from a connection pool at its maximum fails instead of waiting.
+    /// Attribute Data Type: ub1 * [ub1 = c_uchar]
+    ConnectionPoolNoWait = 178,
+
+    /// `OCI_ATTR_CONN_BUSY_COUNT`
+    ///
+    /// Mode: READ
+    ///
+    /// Gets the number of connections of a connection pool that are in use.
+    /// Attribute Data Type: ub4 * [ub4 = c_uint]
+    ConnectionPoolBusyCount = 179,
+
+    /// `OCI_ATTR_CONN_OPEN_COUNT`
+    ///
+    /// Mode: READ
+    ///
+    /// Gets the number of open connections of a connection pool.
+    /// Attribute Data Type: ub4 * [ub4 = c_uint]
+    ConnectionPoolOpenCount = 180,
+
+    /// `OCI_ATTR_CONN_TIMEOUT`
+    ///
+    /// Mode: READ/WRITE
+    ///
+    /// Idle connections of a connection pool above its minimum are closed after this many seconds.
+    /// Attribute Data Type: ub4 * [ub4 = c_uint]
+    ConnectionPoolTimeout = 181,
+
+    /// `OCI_ATTR_STMTCACHESIZE`
+    ///
+    /// Mode: READ/WRITE
+    ///
+    /// Number of statements kept in the statement cache of the service context, 0 turns
+    /// statement caching off.
+    /// Attribute Data Type: ub4 * [ub4 = c_uint]
+    StmtCacheSize = 176,
+
+    /// `OCI_ATTR_UB8_ROW_COUNT`
+    ///
+    /// Mode: READ
+    ///
+    /// Gets the number of rows processed so far by the statement, not limited to 32 bits.
+    /// Attribute Data Type: ub8 * [ub8 = u64]
+    Ub8RowCount = 457,
+
+    /// `OCI_ATTR_ENV_CHARSET_ID`, same as `OCI_ATTR_CHARSET_ID`
+    ///
+    /// Mode: READ
+    ///
+    /// Gets the id of the client character set of the environment.
+    /// Attribute Data Type: ub2 * [ub2 = c_ushort]
+    EnvCharsetId = 31,
+
+    /// `OCI_ATTR_ENV_NCHARSET_ID`, same as `OCI_ATTR_NCHARSET_ID`
+    ///
+    /// Mode: READ
+    ///
+    /// Gets the id of the client national character set of the environment.
+    /// Attribute Data Type: ub2 * [ub2 = c_ushort]
+    EnvNcharsetId = 262,
+
+    /// `OCI_ATTR_SPOOL_TIMEOUT`
+    ///
+    /// Mode: READ/WRITE
+    ///
+    /// Idle sessions of a session pool are terminated after this many seconds, 0 keeps them.
+    /// Attribute Data Type: ub4 * [ub4 = c_uint]
+    SessionPoolTimeout = 308,
+
+    /// `OCI_ATTR_SPOOL_GETMODE`
+    ///
+    /// Mode: READ/WRITE
+    ///
+    /// Behavior of a session pool when all its sessions are busy and it is at its maximum.
+    /// Attribute Data Type: ub1 * [ub1 = c_uchar]
+    SessionPoolGetMode = 309,
+
+    /// `OCI_ATTR_SPOOL_BUSY_COUNT`
+    ///
+    /// Mode: READ
+    ///
+    /// Gets the number of sessions of a session pool that are in use.
+    /// Attribute Data Type: ub4 * [ub4 = c_uint]
+    SessionPoolBusyCount = 310,
+
+    /// `OCI_ATTR_SPOOL_OPEN_COUNT`
+    ///
+    /// Mode: READ
+    ///
+    /// Gets the number of open sessions of a session pool.
+    /// Attribute Data Type: ub4 * [ub4 = c_uint]
+    SessionPoolOpenCount = 311,
+
+    /// `OCI_ATTR_SPOOL_WAIT_TIMEOUT`
+    ///
+    /// Mode: READ/WRITE
+    ///
+    /// Milliseconds a session pool in `OCI_SPOOL_ATTRVAL_WAIT` mode waits for a free session,
+    /// 0 waits indefinitely.
+    /// Attribute Data Type: ub4 * [ub4 = c_uint]
+    SessionPoolWaitTimeout = 506,
 }
 
 /// Type of descriptor
 #[allow(dead_code)]
-enum OCIDescriptorType {
+#[derive(Clone, Copy)]
+pub enum OCIDescriptorType {
+    /// `OCI_DTYPE_LOB`
+    Lob = 50,
+
     /// `OCI_DTYPE_PARAM`
     Parameter = 53,
+
+    /// `OCI_DTYPE_TIMESTAMP`
+    Timestamp = 68,
+
+    /// `OCI_DTYPE_TIMESTAMP_TZ`
+    TimestampWithTz = 70,
+
+    /// `OCI_DTYPE_TIMESTAMP_LTZ`
+    TimestampWithLocalTz = 71,
+
+    /// `OCI_DTYPE_INTERVAL_YM`
+    IntervalYearToMonth = 62,
+
+    /// `OCI_DTYPE_INTERVAL_DS`
+    IntervalDayToSecond = 63,
 }
 
 /// Type of describe attribute
@@ -256,19 +940,32 @@ pub enum OCIDescribeAttribute {
     /// `OCI_ATTR_IS_NULL`: is it null?
     IsNull = 7,
 
+    /// `OCI_ATTR_TYPE_NAME`: name of the object type
+    TypeName = 8,
+
+    /// `OCI_ATTR_SCHEMA_NAME`: schema of the object type
+    SchemaName = 9,
+
     /// `OCI_ATTR_CHAR_USED`: char length semantics
     CharUsed = 285,
 
     /// `OCI_ATTR_CHAR_SIZE`: char length
     CharLength = 286,
+
+    /// `OCI_ATTR_COLLATION_ID`: collation of a character column, from 12.2
+    CollationId = 473,
 }
 
 /// Oracle datatype
 #[allow(dead_code)]
-enum OCIDataType {
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum OCIDataType {
     /// `SQLT_CHR`: (ORANET TYPE) character string
     Char = 1,
 
+    /// `SQLT_DAT`: date in the 7-byte Oracle internal format
+    InternalDate = 12,
+
     /// `SQLT_DATE`: ANSI Date
     Date = 184,
 
@@ -322,17 +1019,87 @@ enum OCIDataType {
 
     /// `SQLT_VNU`: NUM with preceding length byte
     NumericWithLength = 6,
+
+    /// `SQLT_LVC`: character string with a preceding 4-byte length, used to fetch `LONG`
+    LongVarChar = 94,
+
+    /// `SQLT_LVB`: binary data with a preceding 4-byte length, used to fetch `LONG RAW`
+    LongVarRaw = 95,
+}
+
+/// Direction of [`oci_stmt_fetch2`](fn.oci_stmt_fetch2.html)
+#[allow(dead_code)]
+pub enum OCIFetchOrientation {
+    /// `OCI_FETCH_CURRENT`: refetches the current row
+    Current  = 0x01,
+
+    /// `OCI_FETCH_NEXT`: fetches the next row, the only value allowed for non-scrollable cursors
+    Next     = 0x02,
+
+    /// `OCI_FETCH_FIRST`: fetches the first row
+    First    = 0x04,
+
+    /// `OCI_FETCH_LAST`: fetches the last row
+    Last     = 0x08,
+
+    /// `OCI_FETCH_PRIOR`: fetches the row before the current one
+    Prior    = 0x10,
+
+    /// `OCI_FETCH_ABSOLUTE`: fetches the row at the absolute offset
+    Absolute = 0x20,
+
+    /// `OCI_FETCH_RELATIVE`: fetches the row at the offset relative to the current one
+    Relative = 0x40,
+}
+
+/// Allocation callback of [`OCIMemoryCallbacks`](struct.OCIMemoryCallbacks.html).
+pub type OCIMallocFn = extern "C" fn(ctxp: *mut c_void, size: size_t) -> *mut c_void;
+
+/// Reallocation callback of [`OCIMemoryCallbacks`](struct.OCIMemoryCallbacks.html).
+pub type OCIReallocFn = extern "C" fn(ctxp: *mut c_void, memptr: *mut c_void, newsize: size_t) -> *mut c_void;
+
+/// Free callback of [`OCIMemoryCallbacks`](struct.OCIMemoryCallbacks.html).
+pub type OCIFreeFn = extern "C" fn(ctxp: *mut c_void, memptr: *mut c_void);
+
+/// In-bind callback of [`oci_bind_dynamic`](fn.oci_bind_dynamic.html).
+pub type OCICallbackInBind = extern "C" fn(ictxp: *mut c_void, bindp: *mut OCIBind, iter: c_uint, index: c_uint,
+                                           bufpp: *mut *mut c_void, alenp: *mut c_uint, piecep: *mut c_uchar,
+                                           indpp: *mut *mut c_void) -> c_int;
+
+/// Out-bind callback of [`oci_bind_dynamic`](fn.oci_bind_dynamic.html).
+pub type OCICallbackOutBind = extern "C" fn(octxp: *mut c_void, bindp: *mut OCIBind, iter: c_uint, index: c_uint,
+                                            bufpp: *mut *mut c_void, alenpp: *mut *mut c_uint,
+                                            piecep: *mut c_uchar, indpp: *mut *mut c_void,
+                                            rcodepp: *mut *mut c_ushort) -> c_int;
+
+/// User memory functions of [`oci_env_nls_create`](fn.oci_env_nls_create.html).
+///
+/// `context` is passed as `ctxp` to every callback and must stay valid until the environment
+/// handle is freed.
+#[derive(Clone, Copy)]
+pub struct OCIMemoryCallbacks {
+    /// Context of the callbacks.
+    pub context: *mut c_void,
+
+    /// `malocfp`
+    pub malloc:  OCIMallocFn,
+
+    /// `ralocfp`
+    pub realloc: OCIReallocFn,
+
+    /// `mfreefp`
+    pub free:    OCIFreeFn,
 }
 
 #[link(name = "clntsh")]
 extern "C" {
     fn OCIEnvNlsCreate(envp: *mut *mut OCIEnv, mode: c_uint, ctxp: *mut c_void,
-        malocfp: Option<extern "C" fn (ctxp: *mut c_void, size: c_ulong) -> *mut c_void>,
-        ralocfp: Option<extern "C" fn (ctxp: c_void, memptr: c_void, newsize: c_ulong) -> *mut c_void>,
-        mfreefp: Option<extern "C" fn (ctxp: *mut c_void, memptr: *mut c_void)>,
+        malocfp: Option<OCIMallocFn>, ralocfp: Option<OCIReallocFn>, mfreefp: Option<OCIFreeFn>,
         xtramem_sz: c_ulong, usrmempp: *mut *mut c_void, charset: c_ushort,
         ncharset: c_ushort) -> c_int;
 
+    fn OCINlsCharSetNameToId(envhp: *mut c_void, name: *const c_uchar) -> c_ushort;
+
     fn OCIHandleAlloc(parenth: *const c_void, hndlpp: *mut *mut c_void, _type: c_uint,
                       xtramem_sz: c_ulong, usrmempp: *mut *mut c_void) -> c_int;
 
@@ -351,6 +1118,34 @@ extern "C" {
     fn OCISessionEnd(svchp: *mut OCISvcCtx, errhp: *mut OCIError,
                      usrhp: *mut OCISession, mode: c_uint) -> c_int;
 
+    fn OCISessionPoolCreate(envhp: *mut OCIEnv, errhp: *mut OCIError, spoolhp: *mut OCISPool,
+                            poolName: *mut *mut c_uchar, poolNameLen: *mut c_uint,
+                            connStr: *const c_uchar, connStrLen: c_uint, sessMin: c_uint,
+                            sessMax: c_uint, sessIncr: c_uint, userid: *const c_uchar,
+                            useridLen: c_uint, password: *const c_uchar, passwordLen: c_uint,
+                            mode: c_uint) -> c_int;
+
+    fn OCISessionPoolDestroy(spoolhp: *mut OCISPool, errhp: *mut OCIError, mode: c_uint) -> c_int;
+
+    fn OCIConnectionPoolCreate(envhp: *mut OCIEnv, errhp: *mut OCIError, poolhp: *mut OCICPool,
+                               poolName: *mut *mut c_uchar, poolNameLen: *mut c_int,
+                               dblink: *const c_uchar, dblinkLen: c_int, connMin: c_uint,
+                               connMax: c_uint, connIncr: c_uint, poolUserName: *const c_uchar,
+                               poolUserLen: c_int, poolPassword: *const c_uchar, poolPassLen: c_int,
+                               mode: c_uint) -> c_int;
+
+    fn OCIConnectionPoolDestroy(poolhp: *mut OCICPool, errhp: *mut OCIError, mode: c_uint) -> c_int;
+
+    fn OCISessionGet(envhp: *mut OCIEnv, errhp: *mut OCIError, svchp: *mut *mut OCISvcCtx,
+                     authhp: *mut c_void, poolName: *const c_uchar, poolName_len: c_uint,
+                     tagInfo: *const c_uchar, tagInfo_len: c_uint, retTagInfo: *mut *mut c_uchar,
+                     retTagInfo_len: *mut c_uint, found: *mut c_int, mode: c_uint) -> c_int;
+
+    fn OCISessionRelease(svchp: *mut OCISvcCtx, errhp: *mut OCIError, tag: *const c_uchar,
+                         tag_len: c_uint, mode: c_uint) -> c_int;
+
+    fn OCIPing(svchp: *mut OCISvcCtx, errhp: *mut OCIError, mode: c_uint) -> c_int;
+
     fn OCIServerDetach(srvhp: *mut OCIServer, errhp: *mut OCIError, mode: c_uint) -> c_int;
 
     fn OCIHandleFree(hndlp: *mut c_void, _type: c_uint) -> c_int;
@@ -359,6 +1154,10 @@ extern "C" {
                        stmt: *const c_uchar, stmt_len: c_uint, key: *const c_uchar, key_len: c_uint,
                        language: c_uint, mode: c_uint) -> c_int;
 
+    fn OCITransCommit(svchp: *mut OCISvcCtx, errhp: *mut OCIError, flags: c_uint) -> c_int;
+
+    fn OCITransRollback(svchp: *mut OCISvcCtx, errhp: *mut OCIError, flags: c_uint) -> c_int;
+
     fn OCIStmtExecute(svchp: *mut OCISvcCtx, stmtp: *mut OCIStmt, errhp: *mut OCIError,
                       iters: c_uint, rowoff: c_uint, snap_in: *const OCISnapshot,
                       snap_out: *mut OCISnapshot, mode: c_uint) -> c_int;
@@ -371,23 +1170,113 @@ extern "C" {
 
     fn OCIAttrGet(trgthndlp: *const c_void, trghndltyp: c_uint, attributep: *mut c_void,
                   sizep: *mut c_uint, attrtype: c_uint, errhp: *mut OCIError) -> c_int;
+
+    fn OCIDescriptorAlloc(parenth: *const c_void, descpp: *mut *mut c_void, _type: c_uint,
+                          xtramem_sz: size_t, usrmempp: *mut *mut c_void) -> c_int;
+
+    fn OCIDescriptorFree(descp: *mut c_void, _type: c_uint) -> c_int;
+
+    fn OCIDefineByPos(stmtp: *mut OCIStmt, defnpp: *mut *mut OCIDefine, errhp: *mut OCIError,
+                      position: c_uint, valuep: *mut c_void, value_sz: c_int, dty: c_ushort,
+                      indp: *mut c_void, rlenp: *mut c_ushort, rcodep: *mut c_ushort,
+                      mode: c_uint) -> c_int;
+
+    fn OCIStmtFetch2(stmtp: *mut OCIStmt, errhp: *mut OCIError, nrows: c_uint,
+                     orientation: c_ushort, fetch_offset: c_int, mode: c_uint) -> c_int;
+
+    fn OCIDateTimeConstruct(hndl: *mut c_void, err: *mut OCIError, datetime: *mut OCIDateTime,
+                            yr: c_short, mnth: c_uchar, dy: c_uchar, hr: c_uchar, mm: c_uchar,
+                            ss: c_uchar, fsec: c_uint, timezone: *const c_uchar,
+                            timezone_length: size_t) -> c_int;
+
+    fn OCIDateTimeGetDate(hndl: *mut c_void, err: *mut OCIError, date: *const OCIDateTime,
+                          yr: *mut c_short, mnth: *mut c_uchar, dy: *mut c_uchar) -> c_int;
+
+    fn OCIDateTimeGetTime(hndl: *mut c_void, err: *mut OCIError, datetime: *mut OCIDateTime,
+                          hr: *mut c_uchar, mm: *mut c_uchar, ss: *mut c_uchar,
+                          fsec: *mut c_uint) -> c_int;
+
+    fn OCIDateTimeGetTimeZoneOffset(hndl: *mut c_void, err: *mut OCIError,
+                                    datetime: *const OCIDateTime, hour: *mut c_schar,
+                                    minute: *mut c_schar) -> c_int;
+
+    fn OCIDateTimeGetTimeZoneName(hndl: *mut c_void, err: *mut OCIError,
+                                  datetime: *const OCIDateTime, buf: *mut c_uchar,
+                                  buflen: *mut c_uint) -> c_int;
+
+    fn OCIIntervalSetYearMonth(hndl: *mut c_void, err: *mut OCIError, yr: c_int, mnth: c_int,
+                               result: *mut OCIInterval) -> c_int;
+
+    fn OCIIntervalGetYearMonth(hndl: *mut c_void, err: *mut OCIError, yr: *mut c_int, mnth: *mut c_int,
+                               interval: *const OCIInterval) -> c_int;
+
+    fn OCIIntervalSetDaySecond(hndl: *mut c_void, err: *mut OCIError, dy: c_int, hr: c_int, mm: c_int,
+                               ss: c_int, fsec: c_int, result: *mut OCIInterval) -> c_int;
+
+    fn OCIIntervalGetDaySecond(hndl: *mut c_void, err: *mut OCIError, dy: *mut c_int, hr: *mut c_int,
+                               mm: *mut c_int, ss: *mut c_int, fsec: *mut c_int,
+                               interval: *const OCIInterval) -> c_int;
+
+    fn OCILobRead2(svchp: *mut OCISvcCtx, errhp: *mut OCIError, locp: *mut OCILobLocator,
+                   byte_amtp: *mut u64, char_amtp: *mut u64, offset: u64, bufp: *mut c_void, bufl: u64,
+                   piece: c_uchar, ctxp: *mut c_void, cbfp: *mut c_void, csid: c_ushort,
+                   csfrm: c_uchar) -> c_int;
+
+    fn OCILobWrite2(svchp: *mut OCISvcCtx, errhp: *mut OCIError, locp: *mut OCILobLocator,
+                    byte_amtp: *mut u64, char_amtp: *mut u64, offset: u64, bufp: *mut c_void,
+                    buflen: u64, piece: c_uchar, ctxp: *mut c_void, cbfp: *mut c_void, csid: c_ushort,
+                    csfrm: c_uchar) -> c_int;
+
+    fn OCILobGetLength2(svchp: *mut OCISvcCtx, errhp: *mut OCIError, locp: *mut OCILobLocator,
+                        lenp: *mut u64) -> c_int;
+
+    fn OCILobTrim2(svchp: *mut OCISvcCtx, errhp: *mut OCIError, locp: *mut OCILobLocator,
+                   newlen: u64) -> c_int;
+
+    fn OCILobGetChunkSize(svchp: *mut OCISvcCtx, errhp: *mut OCIError, locp: *mut OCILobLocator,
+                          chunk_sizep: *mut c_uint) -> c_int;
+
+    fn OCILobLocatorAssign(svchp: *mut OCISvcCtx, errhp: *mut OCIError, src_locp: *const OCILobLocator,
+                           dst_locpp: *mut *mut OCILobLocator) -> c_int;
+
+    fn OCIBindByPos(stmtp: *mut OCIStmt, bindpp: *mut *mut OCIBind, errhp: *mut OCIError,
+                    position: c_uint, valuep: *mut c_void, value_sz: c_int, dty: c_ushort,
+                    indp: *mut c_void, alenp: *mut c_ushort, rcodep: *mut c_ushort,
+                    maxarr_len: c_uint, curelep: *mut c_uint, mode: c_uint) -> c_int;
+
+    fn OCIBindByName(stmtp: *mut OCIStmt, bindpp: *mut *mut OCIBind, errhp: *mut OCIError,
+                     placeholder: *const c_uchar, placeh_len: c_int, valuep: *mut c_void,
+                     value_sz: c_int, dty: c_ushort, indp: *mut c_void, alenp: *mut c_ushort,
+                     rcodep: *mut c_ushort, maxarr_len: c_uint, curelep: *mut c_uint,
+                     mode: c_uint) -> c_int;
+
+    fn OCIBindDynamic(bindp: *mut OCIBind, errhp: *mut OCIError, ictxp: *mut c_void,
+                      icbfp: OCICallbackInBind, octxp: *mut c_void, ocbfp: OCICallbackOutBind) -> c_int;
 }
 
 /// Binds [`OCIEnvNlsCreate()`](http://docs.oracle.com/cd/E11882_01/appdev.112/e10646/oci16rel001.htm#LNOCI17114).
-pub fn oci_env_nls_create(mode: OCIMode) -> Result<*mut OCIEnv, OracleError> {
+///
+/// `charset` and `ncharset` are character set ids, `0` takes them from `NLS_LANG` and `NLS_NCHAR`.
+/// OCI allocates its memory with `memory` if given and with `malloc()` otherwise.
+/// Fails without calling OCI if the flags of `mode` may not be combined.
+pub fn oci_env_nls_create(mode: OCIMode,
+                          charset: c_ushort,
+                          ncharset: c_ushort,
+                          memory: Option<OCIMemoryCallbacks>) -> Result<*mut OCIEnv, OracleError> {
+    mode.validate()?;
     let mut handle = ptr::null_mut();
     let res = unsafe {
         OCIEnvNlsCreate(
-            &mut handle,     // envp
-            mode as c_uint,  // mode
-            ptr::null_mut(), // ctxp
-            None,            // malocfp
-            None,            // ralocfp
-            None,            // mfreefp
-            0,               // xtramem_sz
-            ptr::null_mut(), // usrmempp
-            0,               // charset
-            0                // ncharset
+            &mut handle,                                             // envp
+            mode.bits(),                                             // mode
+            memory.map_or(ptr::null_mut(), |memory| memory.context), // ctxp
+            memory.map(|memory| memory.malloc),                      // malocfp
+            memory.map(|memory| memory.realloc),                     // ralocfp
+            memory.map(|memory| memory.free),                        // mfreefp
+            0,                                                       // xtramem_sz
+            ptr::null_mut(),                                         // usrmempp
+            charset,                                                 // charset
+            ncharset                                                 // ncharset
         )
     };
     match check_error(res, None, "ffi::oci_env_nls_create") {
@@ -396,6 +1285,25 @@ pub fn oci_env_nls_create(mode: OCIMode) -> Result<*mut OCIEnv, OracleError> {
     }
 }
 
+/// Binds [`OCINlsCharSetNameToId()`](http://docs.oracle.com/cd/E11882_01/appdev.112/e10646/oci22nls003.htm).
+///
+/// Returns the id of the character set `name`, e.g. `873` for `AL32UTF8`.
+pub fn oci_nls_charset_name_to_id(envh: *mut OCIEnv, name: &str) -> Result<c_ushort, OracleError> {
+    let c_name = match CString::new(name) {
+        Ok(c_name) => c_name,
+        Err(_)     => return Err(OracleError::new("Character set name contains a nul byte",
+                                                  "ffi::oci_nls_charset_name_to_id")),
+    };
+    let id = unsafe {
+        OCINlsCharSetNameToId(envh as *mut c_void, c_name.as_ptr() as *const c_uchar)
+    };
+    if id == 0 {
+        return Err(OracleError::new(&format!("Unknown character set {:?}", name),
+                                    "ffi::oci_nls_charset_name_to_id"));
+    }
+    Ok(id)
+}
+
 /// Binds [`OCIHandleAlloc()`](http://docs.oracle.com/cd/E11882_01/appdev.112/e10646/oci16rel002.htm#LNOCI17134).
 pub fn oci_handle_alloc(envh: *mut OCIEnv,
                         htype: OCIHandleType) -> Result<*mut c_void, OracleError> {
@@ -426,7 +1334,7 @@ pub fn oci_server_attach(server_handle: *mut OCIServer,
             error_handle,                  // errhp
             db.as_ptr() as *const c_uchar, // dblink
             db.len() as c_int,             // dblink_len
-            mode as c_uint                 // mode
+            mode.bits()                    // mode
         )
     };
     match check_error(res, Some(error_handle), "ffi::oci_server_attach") {
@@ -436,21 +1344,66 @@ pub fn oci_server_attach(server_handle: *mut OCIServer,
 }
 
 /// Binds [`OCIErrorGet()`](http://docs.oracle.com/cd/E11882_01/appdev.112/e10646/oci17msc007.htm#LNOCI17287).
+///
+/// Reads every record until `OCI_NO_DATA` and the `OCI_ATTR_PARSE_ERROR_OFFSET` of the handle.
+/// Messages are decoded with the encoding of the environment of the last
+/// [`ErrorHandle`](struct.ErrorHandle.html) used on this thread.
 pub fn oci_error_get(error_handle: *mut OCIError, location: &str) -> OracleError {
-    let errc: *mut isize = &mut 0;
-    let buf = String::with_capacity(3072);
+    let encoding = handle::error_encoding();
+    let mut records = Vec::new();
+    let mut buf = vec![0u8; 3072];
+    for recordno in 1.. {
+        let mut errc: c_int = 0;
+        let mut sqlstate = [0u8; 6];
+        let res = unsafe {
+            OCIErrorGet(
+                error_handle as *mut c_void,    // hndlp
+                recordno,                       // recordno
+                sqlstate.as_mut_ptr(),          // sqlstate
+                &mut errc,                      // errcodep
+                buf.as_mut_ptr(),               // bufp
+                buf.len() as c_uint,            // bufsiz
+                OCIHandleType::Error as c_uint  // type
+            )
+        };
+        if res != 0 {
+            break;
+        }
+        let sqlstate = nul_terminated(&sqlstate, Encoding::Ascii);
+        records.push(ErrorRecord {
+            code:     errc as isize,
+            message:  nul_terminated(&buf, encoding).trim_end().to_string(),
+            sqlstate: if sqlstate.is_empty() { None } else { Some(sqlstate) },
+        });
+    }
+    // Read directly, as failing through `check_error` would come back here.
+    let mut parse_offset: c_ushort = 0;
     unsafe {
-        OCIErrorGet(
-            error_handle as *mut c_void,                                  // hndlp
-            1,                                                            // recordno
-            ptr::null_mut(),                                              // sqlstate
-            errc as *mut c_int,                                           // errcodep
-            buf.as_ptr() as *mut c_uchar,                                 // bufp
-            buf.capacity() as c_uint,                                     // bufsiz
-            OCIHandleType::Error as c_uint                                // type
+        OCIAttrGet(
+            error_handle as *const c_void,              // trgthndlp
+            OCIHandleType::Error as c_uint,             // trghndltyp
+            &mut parse_offset as *mut _ as *mut c_void, // attributep
+            ptr::null_mut(),                            // sizep
+            OCIAttribute::ParseErrorOffset as c_uint,   // attrtype
+            error_handle                                // errhp
         )
     };
-    OracleError {code: unsafe { *errc }, message: buf, location: location.to_string()}
+    let mut err = match records.first() {
+        Some(first) => OracleError::with_status(first.code, first.message.clone(), location, OciStatus::Error),
+        None        => OracleError::with_status(-1, "Error with no details".to_string(), location,
+                                                OciStatus::Error),
+    };
+    err.sqlstate = records.first().and_then(|first| first.sqlstate.clone());
+    err.records = records;
+    err.parse_offset = parse_offset;
+    err
+}
+
+/// Text of a nul-terminated buffer filled by OCI, lossily decoded as UTF-8 if it is not valid in
+/// `encoding`.
+fn nul_terminated(buf: &[u8], encoding: Encoding) -> String {
+    let end = buf.iter().position(|&b| b == 0).unwrap_or(buf.len());
+    encoding.decode(&buf[..end]).unwrap_or_else(|_| String::from_utf8_lossy(&buf[..end]).into_owned())
 }
 
 /// Binds [`OCIAttrSet()`](http://docs.oracle.com/cd/E11882_01/appdev.112/e10646/oci16rel002.htm#LNOCI17131).
@@ -482,18 +1435,21 @@ pub fn oci_attr_set(handle: *mut c_void,
 }
 
 /// Binds [`OCISessionBegin()`](http://docs.oracle.com/cd/E11882_01/appdev.112/e10646/oci16rel001.htm#LNOCI17121).
+///
+/// Fails without calling OCI if the flags of `mode` may not be combined.
 pub fn oci_session_begin(service_handle: *mut OCISvcCtx,
                          error_handle: *mut OCIError,
                          session_handle: *mut OCISession,
                          credentials_type: OCICredentialsType,
                          mode: OCIAuthMode) -> Result<(), OracleError> {
+    mode.validate()?;
     let res = unsafe {
         OCISessionBegin(
             service_handle,             // svchp
             error_handle,               // errhp
             session_handle,             // usrhp
             credentials_type as c_uint, // credt
-            mode as c_uint              // mode
+            mode.bits()                 // mode
         )
     };
     match check_error(res, Some(error_handle), "ffi::oci_session_begin") {
@@ -511,7 +1467,7 @@ pub fn oci_session_end(service_handle: *mut OCISvcCtx,
             service_handle,                // svchp
             error_handle,                  // errhp
             session_handle,                // usrhp
-            OCIAuthMode::Default as c_uint // mode
+            OCIAuthMode::Default.bits()    // mode
         )
     };
     match check_error(res, Some(error_handle), "ffi::oci_session_end") {
@@ -524,7 +1480,7 @@ pub fn oci_session_end(service_handle: *mut OCISvcCtx,
 pub fn oci_server_detach(server_handle: *mut OCIServer,
                          error_handle: *mut OCIError) -> Result<(), OracleError> {
     let res = unsafe {
-        OCIServerDetach(server_handle, error_handle, OCIMode::Default as c_uint)
+        OCIServerDetach(server_handle, error_handle, OCIMode::Default.bits())
     };
     match check_error(res, Some(error_handle), "ffi::oci_server_detach") {
         None => Ok(()),
@@ -532,6 +1488,180 @@ pub fn oci_server_detach(server_handle: *mut OCIServer,
     }
 }
 
+/// Binds [`OCISessionPoolCreate()`](http://docs.oracle.com/cd/E11882_01/appdev.112/e10646/oci16rel001.htm).
+///
+/// Creates a homogeneous pool of sessions for `username` in the allocated `pool_handle`.
+/// Texts are in the client character set. Returns the name of the pool, which
+/// [`oci_session_get`](fn.oci_session_get.html) takes sessions from.
+#[allow(clippy::too_many_arguments)]
+pub fn oci_session_pool_create(envh: *mut OCIEnv,
+                               error_handle: *mut OCIError,
+                               pool_handle: *mut OCISPool,
+                               connect_string: &[u8],
+                               min: u32,
+                               max: u32,
+                               increment: u32,
+                               username: &[u8],
+                               password: &[u8]) -> Result<Vec<u8>, OracleError> {
+    let mut name = ptr::null_mut();
+    let mut name_len = 0;
+    let res = unsafe {
+        OCISessionPoolCreate(
+            envh,                                     // envhp
+            error_handle,                             // errhp
+            pool_handle,                              // spoolhp
+            &mut name,                                // poolName
+            &mut name_len,                            // poolNameLen
+            connect_string.as_ptr(),                  // connStr
+            connect_string.len() as c_uint,           // connStrLen
+            min as c_uint,                            // sessMin
+            max as c_uint,                            // sessMax
+            increment as c_uint,                      // sessIncr
+            username.as_ptr(),                        // userid
+            username.len() as c_uint,                 // useridLen
+            password.as_ptr(),                        // password
+            password.len() as c_uint,                 // passwordLen
+            OCISessionPoolMode::Homogeneous as c_uint // mode
+        )
+    };
+    match check_error(res, Some(error_handle), "ffi::oci_session_pool_create") {
+        // The name points into the pool handle, so it is copied.
+        None => Ok(unsafe { slice::from_raw_parts(name as *const u8, name_len as usize) }.to_vec()),
+        Some(err) => Err(err),
+    }
+}
+
+/// Binds [`OCISessionPoolDestroy()`](http://docs.oracle.com/cd/E11882_01/appdev.112/e10646/oci16rel001.htm).
+pub fn oci_session_pool_destroy(pool_handle: *mut OCISPool,
+                                error_handle: *mut OCIError) -> Result<(), OracleError> {
+    let res = unsafe {
+        OCISessionPoolDestroy(pool_handle, error_handle, OCIMode::Default.bits())
+    };
+    match check_error(res, Some(error_handle), "ffi::oci_session_pool_destroy") {
+        None => Ok(()),
+        Some(err) => Err(err),
+    }
+}
+
+/// Binds [`OCIConnectionPoolCreate()`](http://docs.oracle.com/cd/E11882_01/appdev.112/e10646/oci16rel001.htm).
+///
+/// Creates a pool of connections to `connect_string` in the allocated `pool_handle`, opened
+/// as `username`. Texts are in the client character set. Returns the name of the pool, which
+/// [`oci_server_attach`](fn.oci_server_attach.html) attaches to in `OCIMode::ConnectionPool`.
+#[allow(clippy::too_many_arguments)]
+pub fn oci_connection_pool_create(envh: *mut OCIEnv,
+                                  error_handle: *mut OCIError,
+                                  pool_handle: *mut OCICPool,
+                                  connect_string: &[u8],
+                                  min: u32,
+                                  max: u32,
+                                  increment: u32,
+                                  username: &[u8],
+                                  password: &[u8]) -> Result<Vec<u8>, OracleError> {
+    let mut name = ptr::null_mut();
+    let mut name_len = 0;
+    let res = unsafe {
+        OCIConnectionPoolCreate(
+            envh,                          // envhp
+            error_handle,                  // errhp
+            pool_handle,                   // poolhp
+            &mut name,                     // poolName
+            &mut name_len,                 // poolNameLen
+            connect_string.as_ptr(),       // dblink
+            connect_string.len() as c_int, // dblinkLen
+            min as c_uint,                 // connMin
+            max as c_uint,                 // connMax
+            increment as c_uint,           // connIncr
+            username.as_ptr(),             // poolUserName
+            username.len() as c_int,       // poolUserLen
+            password.as_ptr(),             // poolPassword
+            password.len() as c_int,       // poolPassLen
+            OCIMode::Default.bits()        // mode
+        )
+    };
+    match check_error(res, Some(error_handle), "ffi::oci_connection_pool_create") {
+        // The name points into the pool handle, so it is copied.
+        None => Ok(unsafe { slice::from_raw_parts(name as *const u8, name_len as usize) }.to_vec()),
+        Some(err) => Err(err),
+    }
+}
+
+/// Binds [`OCIConnectionPoolDestroy()`](http://docs.oracle.com/cd/E11882_01/appdev.112/e10646/oci16rel001.htm).
+pub fn oci_connection_pool_destroy(pool_handle: *mut OCICPool,
+                                   error_handle: *mut OCIError) -> Result<(), OracleError> {
+    let res = unsafe {
+        OCIConnectionPoolDestroy(pool_handle, error_handle, OCIMode::Default.bits())
+    };
+    match check_error(res, Some(error_handle), "ffi::oci_connection_pool_destroy") {
+        None => Ok(()),
+        Some(err) => Err(err),
+    }
+}
+
+/// Binds [`OCISessionGet()`](http://docs.oracle.com/cd/E11882_01/appdev.112/e10646/oci16rel001.htm)
+/// for session pools.
+///
+/// Returns the service context of a session of the pool `pool_name`, which must be given back
+/// with [`oci_session_release`](fn.oci_session_release.html) rather than freed.
+pub fn oci_session_get(envh: *mut OCIEnv,
+                       error_handle: *mut OCIError,
+                       pool_name: &[u8]) -> Result<*mut OCISvcCtx, OracleError> {
+    let mut service_handle = ptr::null_mut();
+    let res = unsafe {
+        OCISessionGet(
+            envh,                                    // envhp
+            error_handle,                            // errhp
+            &mut service_handle,                     // svchp
+            ptr::null_mut(),                         // authhp
+            pool_name.as_ptr(),                      // poolName
+            pool_name.len() as c_uint,               // poolName_len
+            ptr::null(),                             // tagInfo
+            0,                                       // tagInfo_len
+            ptr::null_mut(),                         // retTagInfo
+            ptr::null_mut(),                         // retTagInfo_len
+            ptr::null_mut(),                         // found
+            OCISessionGetMode::SessionPool as c_uint // mode
+        )
+    };
+    match check_error(res, Some(error_handle), "ffi::oci_session_get") {
+        None => Ok(service_handle),
+        Some(err) => Err(err),
+    }
+}
+
+/// Binds [`OCISessionRelease()`](http://docs.oracle.com/cd/E11882_01/appdev.112/e10646/oci16rel001.htm).
+pub fn oci_session_release(service_handle: *mut OCISvcCtx,
+                           error_handle: *mut OCIError,
+                           mode: OCISessionReleaseMode) -> Result<(), OracleError> {
+    let res = unsafe {
+        OCISessionRelease(
+            service_handle, // svchp
+            error_handle,   // errhp
+            ptr::null(),    // tag
+            0,              // tag_len
+            mode as c_uint  // mode
+        )
+    };
+    match check_error(res, Some(error_handle), "ffi::oci_session_release") {
+        None => Ok(()),
+        Some(err) => Err(err),
+    }
+}
+
+/// Binds [`OCIPing()`](http://docs.oracle.com/cd/E11882_01/appdev.112/e10646/oci16rel001.htm).
+///
+/// Makes a round trip to the server to check that the session is usable.
+pub fn oci_ping(service_handle: *mut OCISvcCtx,
+                error_handle: *mut OCIError) -> Result<(), OracleError> {
+    let res = unsafe {
+        OCIPing(service_handle, error_handle, OCIMode::Default.bits())
+    };
+    match check_error(res, Some(error_handle), "ffi::oci_ping") {
+        None => Ok(()),
+        Some(err) => Err(err),
+    }
+}
+
 /// Binds [`OCIHandleFree()`](http://docs.oracle.com/cd/E11882_01/appdev.112/e10646/oci16rel002.htm#LNOCI17135).
 pub fn oci_handle_free(handle: *mut c_void, htype: OCIHandleType) -> Result<(), OracleError> {
     let res = unsafe {
@@ -544,10 +1674,14 @@ pub fn oci_handle_free(handle: *mut c_void, htype: OCIHandleType) -> Result<(),
 }
 
 /// Binds [`OCIStmtPrepare2()`](http://docs.oracle.com/cd/E11882_01/appdev.112/e10646/oci17msc001.htm#LNOCI17168).
+///
+/// `stmt_text` is in the client character set of the environment. A non-empty `stmt_hash`
+/// is the key the statement is looked up by in the statement cache, instead of its text.
 pub fn oci_stmt_prepare2(service_handle: *mut OCISvcCtx,
                          error_handle: *mut OCIError,
-                         stmt_text: &String,
-                         stmt_hash: &String) -> Result<*mut OCIStmt, OracleError> {
+                         stmt_text: &[u8],
+                         stmt_hash: &String,
+                         mode: OCIStmtPrepare2Mode) -> Result<*mut OCIStmt, OracleError> {
     let mut stmt_handle = ptr::null_mut();
     let res = unsafe {
         OCIStmtPrepare2(
@@ -559,7 +1693,7 @@ pub fn oci_stmt_prepare2(service_handle: *mut OCISvcCtx,
             stmt_hash.as_ptr(),                    // key
             stmt_hash.len() as c_uint,             // key_len
             OCISyntax::NtvSyntax as c_uint,        // language
-            OCIStmtPrepare2Mode::Default as c_uint // mode
+            mode as c_uint                         // mode
         )
     };
     match check_error(res, Some(error_handle), "ffi::oci_stmt_prepare2") {
@@ -569,19 +1703,25 @@ pub fn oci_stmt_prepare2(service_handle: *mut OCISvcCtx,
 }
 
 /// Binds [`OCIStmtExecute()`](http://docs.oracle.com/cd/E11882_01/appdev.112/e10646/oci17msc001.htm#LNOCI17163).
+///
+/// `iters` is 0 for queries and the number of times to execute DML.
+/// Fails without calling OCI if the flags of `mode` may not be combined.
 pub fn oci_stmt_execute(service_handle: *mut OCISvcCtx,
                         stmt_handle: *mut OCIStmt,
-                        error_handle: *mut OCIError) -> Result<(), OracleError> {
+                        error_handle: *mut OCIError,
+                        iters: usize,
+                        mode: OCIExecuteMode) -> Result<(), OracleError> {
+    mode.validate()?;
     let res = unsafe {
         OCIStmtExecute(
-            service_handle,            // svchp
-            stmt_handle,               // stmtp
-            error_handle,              // errhp
-            0 as c_uint,               // iters
-            0 as c_uint,               // rowoff
-            ptr::null(),               // snap_in
-            ptr::null_mut(),           // snap_out
-            OCIMode::Default as c_uint // mode
+            service_handle,  // svchp
+            stmt_handle,     // stmtp
+            error_handle,    // errhp
+            iters as c_uint, // iters
+            0 as c_uint,     // rowoff
+            ptr::null(),     // snap_in
+            ptr::null_mut(), // snap_out
+            mode.bits()      // mode
         )
     };
     match check_error(res, Some(error_handle), "ffi::oci_stmt_execute") {
@@ -590,17 +1730,52 @@ pub fn oci_stmt_execute(service_handle: *mut OCISvcCtx,
     }
 }
 
+/// Binds [`OCITransCommit()`](http://docs.oracle.com/cd/E11882_01/appdev.112/e10646/oci17msc006.htm).
+pub fn oci_trans_commit(service_handle: *mut OCISvcCtx,
+                        error_handle: *mut OCIError) -> Result<(), OracleError> {
+    let res = unsafe {
+        OCITransCommit(
+            service_handle, // svchp
+            error_handle,   // errhp
+            0 as c_uint     // flags: OCI_DEFAULT
+        )
+    };
+    match check_error(res, Some(error_handle), "ffi::oci_trans_commit") {
+        None => Ok(()),
+        Some(err) => Err(err),
+    }
+}
+
+/// Binds [`OCITransRollback()`](http://docs.oracle.com/cd/E11882_01/appdev.112/e10646/oci17msc006.htm).
+pub fn oci_trans_rollback(service_handle: *mut OCISvcCtx,
+                          error_handle: *mut OCIError) -> Result<(), OracleError> {
+    let res = unsafe {
+        OCITransRollback(
+            service_handle, // svchp
+            error_handle,   // errhp
+            0 as c_uint     // flags: OCI_DEFAULT
+        )
+    };
+    match check_error(res, Some(error_handle), "ffi::oci_trans_rollback") {
+        None => Ok(()),
+        Some(err) => Err(err),
+    }
+}
+
 /// Binds [`OCIStmtRelease()`](http://docs.oracle.com/cd/E11882_01/appdev.112/e10646/oci17msc001.htm#LNOCI17169).
+///
+/// A non-empty `stmt_hash` is the key the statement is cached by.
 pub fn oci_stmt_release(stmt_handle: *mut OCIStmt,
                         error_handle: *mut OCIError,
-                        stmt_hash: &String) -> Result<(), OracleError> {
+                        stmt_hash: &String,
+                        mode: OCIStmtReleaseMode) -> Result<(), OracleError> {
     let res = unsafe {
         OCIStmtRelease(
             stmt_handle,               // stmtp
             error_handle,              // errhp
             stmt_hash.as_ptr(),        // key
             stmt_hash.len() as c_uint, // keylen
-            OCIMode::Default as c_uint // mode
+            mode as c_uint             // mode
         )
     };
     match check_error(res, Some(error_handle), "ffi::oci_stmt_release") {
@@ -629,20 +1804,47 @@ pub fn oci_param_get(stmt_handle: *mut OCIStmt,
     }
 }
 
-/// Binds [`OCIAttrGet()`](http://docs.oracle.com/cd/E11882_01/appdev.112/e10646/oci16rel002.htm#LNOCI17130).
-pub fn oci_attr_get(attr_handle: *mut c_void,
-                    error_handle: *mut OCIError,
-                    attr_type: OCIDescribeAttribute) -> Result<(*mut c_void, isize), OracleError> {
-    let attribute = ptr::null_mut();
+/// Binds [`OCIParamGet()`](http://docs.oracle.com/cd/E11882_01/appdev.112/e10646/oci16rel002.htm#LNOCI17136)
+/// for the row errors of an array DML executed with `OCI_BATCH_ERRORS`.
+///
+/// `row_error` must be an allocated error handle, the error at zero-based `index` is read into it.
+pub fn oci_batch_error_get(error_handle: *mut OCIError,
+                           row_error: *mut OCIError,
+                           index: usize) -> Result<*mut OCIError, OracleError> {
+    let mut row_error = row_error as *mut c_void;
+    let res = unsafe {
+        OCIParamGet(
+            error_handle as *const _,       // hndlp
+            OCIHandleType::Error as c_uint, // htype
+            error_handle,                   // errhp
+            &mut row_error,                 // parmdpp
+            index as c_uint                 // pos
+        )
+    };
+    match check_error(res, Some(error_handle), "ffi::oci_batch_error_get") {
+        None => Ok(row_error as *mut OCIError),
+        Some(err) => Err(err),
+    }
+}
+
+/// Binds [`OCIAttrGet()`](http://docs.oracle.com/cd/E11882_01/appdev.112/e10646/oci16rel002.htm#LNOCI17130)
+/// for parameter descriptors.
+///
+/// `T` is the attribute data type as documented for `attr_type` and must be a plain C type,
+/// e.g. `c_ushort` for `OCI_ATTR_DATA_TYPE` or `*mut c_uchar` for `OCI_ATTR_NAME`.
+pub fn oci_attr_get<T: Copy>(attr_handle: *mut c_void,
+                             error_handle: *mut OCIError,
+                             attr_type: OCIDescribeAttribute) -> Result<(T, isize), OracleError> {
+    let mut attribute: T = unsafe { mem::zeroed() };
     let mut attribute_size = 0;
     let res = unsafe {
         OCIAttrGet(
-            attr_handle as *const _,                // trgthndlp
-            OCIDescriptorType::Parameter as c_uint, // trghndltyp
-            attribute,                              // attributep
-            &mut attribute_size,                    // sizep
-            attr_type as c_uint,                    // attrtype
-            error_handle                            // errhp
+            attr_handle as *const _,                   // trgthndlp
+            OCIDescriptorType::Parameter as c_uint,    // trghndltyp
+            &mut attribute as *mut T as *mut c_void,   // attributep
+            &mut attribute_size,                       // sizep
+            attr_type as c_uint,                       // attrtype
+            error_handle                               // errhp
         )
     };
     match check_error(res, Some(error_handle), "ffi::oci_attr_get") {
@@ -651,37 +1853,691 @@ pub fn oci_attr_get(attr_handle: *mut c_void,
     }
 }
 
+/// Binds [`OCIAttrGet()`](http://docs.oracle.com/cd/E11882_01/appdev.112/e10646/oci16rel002.htm#LNOCI17130)
+/// for handles.
+///
+/// `T` is the attribute data type as documented for `attr_type` and must be a plain C type.
+pub fn oci_handle_attr_get<T: Copy>(handle: *mut c_void,
+                                    htype: OCIHandleType,
+                                    attr_type: OCIAttribute,
+                                    error_handle: *mut OCIError) -> Result<(T, isize), OracleError> {
+    let mut attribute: T = unsafe { mem::zeroed() };
+    let mut attribute_size = 0;
+    let res = unsafe {
+        OCIAttrGet(
+            handle as *const _,                        // trgthndlp
+            htype as c_uint,                           // trghndltyp
+            &mut attribute as *mut T as *mut c_void,   // attributep
+            &mut attribute_size,                       // sizep
+            attr_type as c_uint,                       // attrtype
+            error_handle                               // errhp
+        )
+    };
+    match check_error(res, Some(error_handle), "ffi::oci_handle_attr_get") {
+        None => Ok((attribute, attribute_size as isize)),
+        Some(err) => Err(err),
+    }
+}
+
+/// Binds [`OCIDescriptorAlloc()`](http://docs.oracle.com/cd/E11882_01/appdev.112/e10646/oci16rel002.htm#LNOCI17132).
+pub fn oci_descriptor_alloc(envh: *mut OCIEnv,
+                            dtype: OCIDescriptorType) -> Result<*mut c_void, OracleError> {
+    let mut descriptor = ptr::null_mut();
+    let res = unsafe {
+        OCIDescriptorAlloc(
+            envh as *const _, // parenth
+            &mut descriptor,  // descpp
+            dtype as c_uint,  // type
+            0,                // xtramem_sz
+            ptr::null_mut()   // usrmempp
+        )
+    };
+    match check_error(res, None, "ffi::oci_descriptor_alloc") {
+        None => Ok(descriptor),
+        Some(err) => Err(err),
+    }
+}
+
+/// Binds [`OCIDescriptorFree()`](http://docs.oracle.com/cd/E11882_01/appdev.112/e10646/oci16rel002.htm#LNOCI17133).
+pub fn oci_descriptor_free(descriptor: *mut c_void,
+                           dtype: OCIDescriptorType) -> Result<(), OracleError> {
+    let res = unsafe {
+        OCIDescriptorFree(descriptor, dtype as c_uint)
+    };
+    match check_error(res, None, "ffi::oci_descriptor_free") {
+        None => Ok(()),
+        Some(err) => Err(err),
+    }
+}
+
+/// Binds [`OCIDefineByPos()`](http://docs.oracle.com/cd/E11882_01/appdev.112/e10646/oci16rel003.htm#LNOCI17144).
+///
+/// `value`, `indicator`, `length` and `return_code` must stay valid until the last fetch.
+#[allow(clippy::too_many_arguments)]
+pub fn oci_define_by_pos(stmt_handle: *mut OCIStmt,
+                         error_handle: *mut OCIError,
+                         position: usize,
+                         value: *mut c_void,
+                         value_size: usize,
+                         data_type: OCIDataType,
+                         indicator: *mut c_short,
+                         length: *mut c_ushort,
+                         return_code: *mut c_ushort) -> Result<*mut OCIDefine, OracleError> {
+    let mut define_handle = ptr::null_mut();
+    let res = unsafe {
+        OCIDefineByPos(
+            stmt_handle,                // stmtp
+            &mut define_handle,         // defnpp
+            error_handle,               // errhp
+            position as c_uint,         // position
+            value,                      // valuep
+            value_size as c_int,        // value_sz
+            data_type as c_ushort,      // dty
+            indicator as *mut c_void,   // indp
+            length,                     // rlenp
+            return_code,                // rcodep
+            OCIMode::Default.bits()     // mode
+        )
+    };
+    match check_error(res, Some(error_handle), "ffi::oci_define_by_pos") {
+        None => Ok(define_handle),
+        Some(err) => Err(err),
+    }
+}
+
+/// Binds [`OCIStmtFetch2()`](http://docs.oracle.com/cd/E11882_01/appdev.112/e10646/oci17msc001.htm#LNOCI17165).
+///
+/// Returns an error with code `100` ("No data") once the result set is exhausted.
+pub fn oci_stmt_fetch2(stmt_handle: *mut OCIStmt,
+                       error_handle: *mut OCIError,
+                       rows: usize,
+                       orientation: OCIFetchOrientation,
+                       offset: isize) -> Result<(), OracleError> {
+    let res = unsafe {
+        OCIStmtFetch2(
+            stmt_handle,                // stmtp
+            error_handle,               // errhp
+            rows as c_uint,             // nrows
+            orientation as c_ushort,    // orientation
+            offset as c_int,            // fetchOffset
+            OCIMode::Default.bits()     // mode
+        )
+    };
+    match check_error(res, Some(error_handle), "ffi::oci_stmt_fetch2") {
+        None => Ok(()),
+        Some(err) => Err(err),
+    }
+}
+
+/// Binds [`OCIDateTimeConstruct()`](http://docs.oracle.com/cd/E11882_01/appdev.112/e10646/oci19map003.htm).
+///
+/// `time_zone` is an offset like `+02:00` or a region name like `Europe/Paris`.
+pub fn oci_date_time_construct(envh: *mut OCIEnv,
+                               error_handle: *mut OCIError,
+                               datetime: *mut OCIDateTime,
+                               date: (i16, u8, u8),
+                               time: (u8, u8, u8, u32),
+                               time_zone: Option<&str>) -> Result<(), OracleError> {
+    let (year, month, day) = date;
+    let (hour, minute, second, fsec) = time;
+    let res = unsafe {
+        OCIDateTimeConstruct(
+            envh as *mut c_void,                                   // hndl
+            error_handle,                                          // err
+            datetime,                                              // datetime
+            year as c_short,                                       // yr
+            month as c_uchar,                                      // mnth
+            day as c_uchar,                                        // dy
+            hour as c_uchar,                                       // hr
+            minute as c_uchar,                                     // mm
+            second as c_uchar,                                     // ss
+            fsec as c_uint,                                        // fsec
+            time_zone.map_or(ptr::null(), |tz| tz.as_ptr()),       // timezone
+            time_zone.map_or(0, |tz| tz.len()) as size_t           // timezone_length
+        )
+    };
+    match check_error(res, Some(error_handle), "ffi::oci_date_time_construct") {
+        None => Ok(()),
+        Some(err) => Err(err),
+    }
+}
+
+/// Binds [`OCIDateTimeGetDate()`](http://docs.oracle.com/cd/E11882_01/appdev.112/e10646/oci19map003.htm).
+///
+/// Returns year, month and day.
+pub fn oci_date_time_get_date(envh: *mut OCIEnv,
+                              error_handle: *mut OCIError,
+                              datetime: *mut OCIDateTime) -> Result<(i16, u8, u8), OracleError> {
+    let (mut year, mut month, mut day) = (0, 0, 0);
+    let res = unsafe {
+        OCIDateTimeGetDate(envh as *mut c_void, error_handle, datetime, &mut year, &mut month, &mut day)
+    };
+    match check_error(res, Some(error_handle), "ffi::oci_date_time_get_date") {
+        None => Ok((year, month, day)),
+        Some(err) => Err(err),
+    }
+}
+
+/// Binds [`OCIDateTimeGetTime()`](http://docs.oracle.com/cd/E11882_01/appdev.112/e10646/oci19map003.htm).
+///
+/// Returns hour, minute, second and fractional second in nanoseconds.
+pub fn oci_date_time_get_time(envh: *mut OCIEnv,
+                              error_handle: *mut OCIError,
+                              datetime: *mut OCIDateTime) -> Result<(u8, u8, u8, u32), OracleError> {
+    let (mut hour, mut minute, mut second, mut fsec) = (0, 0, 0, 0);
+    let res = unsafe {
+        OCIDateTimeGetTime(envh as *mut c_void, error_handle, datetime,
+                           &mut hour, &mut minute, &mut second, &mut fsec)
+    };
+    match check_error(res, Some(error_handle), "ffi::oci_date_time_get_time") {
+        None => Ok((hour, minute, second, fsec)),
+        Some(err) => Err(err),
+    }
+}
+
+/// Binds [`OCIDateTimeGetTimeZoneOffset()`](http://docs.oracle.com/cd/E11882_01/appdev.112/e10646/oci19map003.htm).
+///
+/// Returns hour and minute of the offset from UTC.
+pub fn oci_date_time_get_time_zone_offset(envh: *mut OCIEnv,
+                                          error_handle: *mut OCIError,
+                                          datetime: *mut OCIDateTime) -> Result<(i8, i8), OracleError> {
+    let (mut hour, mut minute) = (0, 0);
+    let res = unsafe {
+        OCIDateTimeGetTimeZoneOffset(envh as *mut c_void, error_handle, datetime, &mut hour, &mut minute)
+    };
+    match check_error(res, Some(error_handle), "ffi::oci_date_time_get_time_zone_offset") {
+        None => Ok((hour, minute)),
+        Some(err) => Err(err),
+    }
+}
+
+/// Binds [`OCIDateTimeGetTimeZoneName()`](http://docs.oracle.com/cd/E11882_01/appdev.112/e10646/oci19map003.htm).
+///
+/// Returns the region name, or the offset like `+02:00` if the value has no region.
+pub fn oci_date_time_get_time_zone_name(envh: *mut OCIEnv,
+                                        error_handle: *mut OCIError,
+                                        datetime: *mut OCIDateTime) -> Result<String, OracleError> {
+    let mut buf = vec![0u8; 64];
+    let mut buf_len = buf.len() as c_uint;
+    let res = unsafe {
+        OCIDateTimeGetTimeZoneName(envh as *mut c_void, error_handle, datetime,
+                                   buf.as_mut_ptr(), &mut buf_len)
+    };
+    match check_error(res, Some(error_handle), "ffi::oci_date_time_get_time_zone_name") {
+        None => {
+            buf.truncate(buf_len as usize);
+            Ok(String::from_utf8_lossy(&buf).into_owned())
+        },
+        Some(err) => Err(err),
+    }
+}
+
+/// Binds [`OCIIntervalSetYearMonth()`](http://docs.oracle.com/cd/E11882_01/appdev.112/e10646/oci19map004.htm).
+pub fn oci_interval_set_year_month(envh: *mut OCIEnv,
+                                   error_handle: *mut OCIError,
+                                   interval: *mut OCIInterval,
+                                   years: i32,
+                                   months: i32) -> Result<(), OracleError> {
+    let res = unsafe {
+        OCIIntervalSetYearMonth(
+            envh as *mut c_void, // hndl
+            error_handle,        // err
+            years as c_int,      // yr
+            months as c_int,     // mnth
+            interval             // result
+        )
+    };
+    match check_error(res, Some(error_handle), "ffi::oci_interval_set_year_month") {
+        None => Ok(()),
+        Some(err) => Err(err),
+    }
+}
+
+/// Binds [`OCIIntervalGetYearMonth()`](http://docs.oracle.com/cd/E11882_01/appdev.112/e10646/oci19map004.htm).
+///
+/// Returns years and months.
+pub fn oci_interval_get_year_month(envh: *mut OCIEnv,
+                                   error_handle: *mut OCIError,
+                                   interval: *mut OCIInterval) -> Result<(i32, i32), OracleError> {
+    let (mut years, mut months) = (0, 0);
+    let res = unsafe {
+        OCIIntervalGetYearMonth(envh as *mut c_void, error_handle, &mut years, &mut months, interval)
+    };
+    match check_error(res, Some(error_handle), "ffi::oci_interval_get_year_month") {
+        None => Ok((years, months)),
+        Some(err) => Err(err),
+    }
+}
+
+/// Binds [`OCIIntervalSetDaySecond()`](http://docs.oracle.com/cd/E11882_01/appdev.112/e10646/oci19map004.htm).
+///
+/// `day_second` is days, hours, minutes, seconds and fractional second in nanoseconds.
+pub fn oci_interval_set_day_second(envh: *mut OCIEnv,
+                                   error_handle: *mut OCIError,
+                                   interval: *mut OCIInterval,
+                                   day_second: (i32, i32, i32, i32, i32)) -> Result<(), OracleError> {
+    let (days, hours, minutes, seconds, fsec) = day_second;
+    let res = unsafe {
+        OCIIntervalSetDaySecond(
+            envh as *mut c_void, // hndl
+            error_handle,        // err
+            days as c_int,       // dy
+            hours as c_int,      // hr
+            minutes as c_int,    // mm
+            seconds as c_int,    // ss
+            fsec as c_int,       // fsec
+            interval             // result
+        )
+    };
+    match check_error(res, Some(error_handle), "ffi::oci_interval_set_day_second") {
+        None => Ok(()),
+        Some(err) => Err(err),
+    }
+}
+
+/// Binds [`OCIIntervalGetDaySecond()`](http://docs.oracle.com/cd/E11882_01/appdev.112/e10646/oci19map004.htm).
+///
+/// Returns days, hours, minutes, seconds and fractional second in nanoseconds.
+pub fn oci_interval_get_day_second(envh: *mut OCIEnv,
+                                   error_handle: *mut OCIError,
+                                   interval: *mut OCIInterval)
+                                   -> Result<(i32, i32, i32, i32, i32), OracleError> {
+    let (mut days, mut hours, mut minutes, mut seconds, mut fsec) = (0, 0, 0, 0, 0);
+    let res = unsafe {
+        OCIIntervalGetDaySecond(envh as *mut c_void, error_handle,
+                                &mut days, &mut hours, &mut minutes, &mut seconds, &mut fsec, interval)
+    };
+    match check_error(res, Some(error_handle), "ffi::oci_interval_get_day_second") {
+        None => Ok((days, hours, minutes, seconds, fsec)),
+        Some(err) => Err(err),
+    }
+}
+
+/// Binds [`OCILobRead2()`](http://docs.oracle.com/cd/E11882_01/appdev.112/e10646/oci17msc002.htm).
+///
+/// Reads up to `buf.len()` bytes in one piece starting at the 1-based `offset`, which is in
+/// characters for `CLOB`. Returns the number of bytes and characters read.
+pub fn oci_lob_read2(service_handle: *mut OCISvcCtx,
+                     error_handle: *mut OCIError,
+                     locator: *mut OCILobLocator,
+                     offset: u64,
+                     buf: &mut [u8]) -> Result<(u64, u64), OracleError> {
+    let mut byte_amount = buf.len() as u64;
+    let mut char_amount = 0;
+    let res = unsafe {
+        OCILobRead2(
+            service_handle,                   // svchp
+            error_handle,                     // errhp
+            locator,                          // locp
+            &mut byte_amount,                 // byte_amtp
+            &mut char_amount,                 // char_amtp
+            offset,                           // offset
+            buf.as_mut_ptr() as *mut c_void,  // bufp
+            buf.len() as u64,                 // bufl
+            0,                                // piece: OCI_ONE_PIECE
+            ptr::null_mut(),                  // ctxp
+            ptr::null_mut(),                  // cbfp
+            0,                                // csid
+            1                                 // csfrm: SQLCS_IMPLICIT
+        )
+    };
+    match check_error(res, Some(error_handle), "ffi::oci_lob_read2") {
+        None => Ok((byte_amount, char_amount)),
+        Some(err) => Err(err),
+    }
+}
+
+/// Binds [`OCILobWrite2()`](http://docs.oracle.com/cd/E11882_01/appdev.112/e10646/oci17msc002.htm).
+///
+/// Writes `buf` in one piece starting at the 1-based `offset`, which is in characters for `CLOB`.
+/// Returns the number of bytes and characters written.
+pub fn oci_lob_write2(service_handle: *mut OCISvcCtx,
+                      error_handle: *mut OCIError,
+                      locator: *mut OCILobLocator,
+                      offset: u64,
+                      buf: &[u8]) -> Result<(u64, u64), OracleError> {
+    let mut byte_amount = buf.len() as u64;
+    let mut char_amount = 0;
+    let res = unsafe {
+        OCILobWrite2(
+            service_handle,              // svchp
+            error_handle,                // errhp
+            locator,                     // locp
+            &mut byte_amount,            // byte_amtp
+            &mut char_amount,            // char_amtp
+            offset,                      // offset
+            buf.as_ptr() as *mut c_void, // bufp
+            buf.len() as u64,            // buflen
+            0,                           // piece: OCI_ONE_PIECE
+            ptr::null_mut(),             // ctxp
+            ptr::null_mut(),             // cbfp
+            0,                           // csid
+            1                            // csfrm: SQLCS_IMPLICIT
+        )
+    };
+    match check_error(res, Some(error_handle), "ffi::oci_lob_write2") {
+        None => Ok((byte_amount, char_amount)),
+        Some(err) => Err(err),
+    }
+}
+
+/// Binds [`OCILobGetLength2()`](http://docs.oracle.com/cd/E11882_01/appdev.112/e10646/oci17msc002.htm).
+///
+/// The length is in bytes for `BLOB` and in characters for `CLOB`.
+pub fn oci_lob_get_length2(service_handle: *mut OCISvcCtx,
+                           error_handle: *mut OCIError,
+                           locator: *mut OCILobLocator) -> Result<u64, OracleError> {
+    let mut length = 0;
+    let res = unsafe {
+        OCILobGetLength2(service_handle, error_handle, locator, &mut length)
+    };
+    match check_error(res, Some(error_handle), "ffi::oci_lob_get_length2") {
+        None => Ok(length),
+        Some(err) => Err(err),
+    }
+}
+
+/// Binds [`OCILobTrim2()`](http://docs.oracle.com/cd/E11882_01/appdev.112/e10646/oci17msc002.htm).
+pub fn oci_lob_trim2(service_handle: *mut OCISvcCtx,
+                     error_handle: *mut OCIError,
+                     locator: *mut OCILobLocator,
+                     length: u64) -> Result<(), OracleError> {
+    let res = unsafe {
+        OCILobTrim2(service_handle, error_handle, locator, length)
+    };
+    match check_error(res, Some(error_handle), "ffi::oci_lob_trim2") {
+        None => Ok(()),
+        Some(err) => Err(err),
+    }
+}
+
+/// Binds [`OCILobGetChunkSize()`](http://docs.oracle.com/cd/E11882_01/appdev.112/e10646/oci17msc002.htm).
+pub fn oci_lob_get_chunk_size(service_handle: *mut OCISvcCtx,
+                              error_handle: *mut OCIError,
+                              locator: *mut OCILobLocator) -> Result<u32, OracleError> {
+    let mut chunk_size = 0;
+    let res = unsafe {
+        OCILobGetChunkSize(service_handle, error_handle, locator, &mut chunk_size)
+    };
+    match check_error(res, Some(error_handle), "ffi::oci_lob_get_chunk_size") {
+        None => Ok(chunk_size),
+        Some(err) => Err(err),
+    }
+}
+
+/// Binds [`OCILobLocatorAssign()`](http://docs.oracle.com/cd/E11882_01/appdev.112/e10646/oci17msc002.htm).
+///
+/// `destination` must point to an allocated locator.
+pub fn oci_lob_locator_assign(service_handle: *mut OCISvcCtx,
+                              error_handle: *mut OCIError,
+                              source: *mut OCILobLocator,
+                              destination: *mut *mut OCILobLocator) -> Result<(), OracleError> {
+    let res = unsafe {
+        OCILobLocatorAssign(service_handle, error_handle, source, destination)
+    };
+    match check_error(res, Some(error_handle), "ffi::oci_lob_locator_assign") {
+        None => Ok(()),
+        Some(err) => Err(err),
+    }
+}
+
+/// Binds [`OCIBindByPos()`](http://docs.oracle.com/cd/E11882_01/appdev.112/e10646/oci16rel003.htm#LNOCI17140).
+///
+/// `value`, `indicator` and `length` must stay valid until the statement is executed.
+/// `length` may be null, then `value_size` is used as the length of the value.
+#[allow(clippy::too_many_arguments)]
+pub fn oci_bind_by_pos(stmt_handle: *mut OCIStmt,
+                       error_handle: *mut OCIError,
+                       position: usize,
+                       value: *mut c_void,
+                       value_size: usize,
+                       data_type: OCIDataType,
+                       indicator: *mut c_short,
+                       length: *mut c_ushort,
+                       mode: OCIBindMode) -> Result<*mut OCIBind, OracleError> {
+    let mut bind_handle = ptr::null_mut();
+    let res = unsafe {
+        OCIBindByPos(
+            stmt_handle,                // stmtp
+            &mut bind_handle,           // bindpp
+            error_handle,               // errhp
+            position as c_uint,         // position
+            value,                      // valuep
+            value_size as c_int,        // value_sz
+            data_type as c_ushort,      // dty
+            indicator as *mut c_void,   // indp
+            length,                     // alenp
+            ptr::null_mut(),            // rcodep
+            0,                          // maxarr_len
+            ptr::null_mut(),            // curelep
+            mode as c_uint              // mode
+        )
+    };
+    match check_error(res, Some(error_handle), "ffi::oci_bind_by_pos") {
+        None => Ok(bind_handle),
+        Some(err) => Err(err),
+    }
+}
+
+/// Binds [`OCIBindByName()`](http://docs.oracle.com/cd/E11882_01/appdev.112/e10646/oci16rel003.htm#LNOCI17139).
+///
+/// `placeholder` is the name of the placeholder including the leading colon, e.g. `:id`.
+/// `value`, `indicator` and `length` must stay valid until the statement is executed.
+/// `length` may be null, then `value_size` is used as the length of the value.
+#[allow(clippy::too_many_arguments)]
+pub fn oci_bind_by_name(stmt_handle: *mut OCIStmt,
+                        error_handle: *mut OCIError,
+                        placeholder: &str,
+                        value: *mut c_void,
+                        value_size: usize,
+                        data_type: OCIDataType,
+                        indicator: *mut c_short,
+                        length: *mut c_ushort,
+                        mode: OCIBindMode) -> Result<*mut OCIBind, OracleError> {
+    let mut bind_handle = ptr::null_mut();
+    let res = unsafe {
+        OCIBindByName(
+            stmt_handle,                // stmtp
+            &mut bind_handle,           // bindpp
+            error_handle,               // errhp
+            placeholder.as_ptr(),       // placeholder
+            placeholder.len() as c_int, // placeh_len
+            value,                      // valuep
+            value_size as c_int,        // value_sz
+            data_type as c_ushort,      // dty
+            indicator as *mut c_void,   // indp
+            length,                     // alenp
+            ptr::null_mut(),            // rcodep
+            0,                          // maxarr_len
+            ptr::null_mut(),            // curelep
+            mode as c_uint              // mode
+        )
+    };
+    match check_error(res, Some(error_handle), "ffi::oci_bind_by_name") {
+        None => Ok(bind_handle),
+        Some(err) => Err(err),
+    }
+}
+
+/// Binds [`OCIBindDynamic()`](http://docs.oracle.com/cd/E11882_01/appdev.112/e10646/oci16rel003.htm).
+///
+/// Registers the callbacks providing the values of a bind made in `OCIBindMode::DataAtExec`.
+/// The contexts are passed to the callbacks and must stay valid until the statement is executed.
+pub fn oci_bind_dynamic(bind_handle: *mut OCIBind,
+                        error_handle: *mut OCIError,
+                        in_context: *mut c_void,
+                        in_callback: OCICallbackInBind,
+                        out_context: *mut c_void,
+                        out_callback: OCICallbackOutBind) -> Result<(), OracleError> {
+    let res = unsafe {
+        OCIBindDynamic(
+            bind_handle,  // bindp
+            error_handle, // errhp
+            in_context,   // ictxp
+            in_callback,  // icbfp
+            out_context,  // octxp
+            out_callback  // ocbfp
+        )
+    };
+    match check_error(res, Some(error_handle), "ffi::oci_bind_dynamic") {
+        None => Ok(()),
+        Some(err) => Err(err),
+    }
+}
+
 /// Convert oracle error codes to [`OracleError`](struct.OracleError.html).
+///
+/// Returns `None` for `OCI_SUCCESS` and `OCI_SUCCESS_WITH_INFO`. The diagnostics of a success with
+/// info are read from the error handle as a warning, which calls made through
+/// [`ErrorHandle::call`](struct.ErrorHandle.html#method.call) keep with the handle. The error handle
+/// is only read for these two codes and `OCI_ERROR`, any other code is reported with its
+/// [`OciStatus`](enum.OciStatus.html).
 pub fn check_error(code: c_int,
                    error_handle: Option<*mut OCIError>,
                    location: &str) -> Option<OracleError> {
-    let by_handle = match error_handle {
-        Some(handle) => Some(oci_error_get(handle, location)),
-        None         => None,
-    };
-    match code {
-        0     => None,
-        100   => Some(OracleError {
-            code: code as isize, message: "No data".to_string(), location: location.to_string()
-        }),
-        -2    => Some(OracleError {
-            code: code as isize, message: "Invalid handle".to_string(), location: location.to_string()
-        }),
-        99    => Some(OracleError {
-            code: code as isize, message: "Need data".to_string(), location: location.to_string()
-        }),
-        -3123 => Some(OracleError {
-            code: code as isize, message: "Still executing".to_string(),
-            location: location.to_string()
-        }),
-        -1    => Some(by_handle.unwrap_or(OracleError {
-            code: code as isize, message: "Error with no details".to_string(),
-            location: location.to_string()
-        })),
-        1     => Some(by_handle.unwrap_or(OracleError {
-            code: code as isize, message: "Success with info".to_string(),
-            location: location.to_string()
-        })),
-        _     => panic!("Unknown return code"),
+    let kind = OciStatus::from_code(code);
+    let message = match kind {
+        OciStatus::Success         => return None,
+        OciStatus::SuccessWithInfo => {
+            if let Some(handle) = error_handle {
+                let mut warning = oci_error_get(handle, location);
+                warning.kind = kind;
+                handle::set_warning(warning);
+            }
+            return None;
+        },
+        OciStatus::Error => match error_handle {
+            Some(handle) => return Some(oci_error_get(handle, location)),
+            None         => "Error with no details".to_string(),
+        },
+        OciStatus::NoData          => "No data".to_string(),
+        OciStatus::InvalidHandle   => "Invalid handle".to_string(),
+        OciStatus::NeedData        => "Need data".to_string(),
+        OciStatus::StillExecuting  => "Still executing".to_string(),
+        OciStatus::Continue        => "Continue".to_string(),
+        OciStatus::RowCallbackDone => "Row callback done".to_string(),
+        OciStatus::Unknown(code)   => format!("Unknown return code {}", code),
+    };
+    Some(OracleError::with_status(code as isize, message, location, kind))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ErrorRecord, OciStatus, OracleError, OCIMode, OCIAuthMode, OCIExecuteMode};
+
+    fn error(code: isize) -> OracleError {
+        OracleError::with_status(code, format!("ORA-{:05}", code), "test", OciStatus::Error)
+    }
+
+    #[test]
+    fn classifies_lost_connections() {
+        for &code in &[3113, 3114, 3135, 28, 1012, 12537, 12547] {
+            let err = error(code);
+            assert!(err.is_connection_lost(), "{}", code);
+            assert!(err.is_retryable(), "{}", code);
+        }
+        assert!(!error(12541).is_connection_lost());
+    }
+
+    #[test]
+    fn classifies_deadlocks() {
+        let err = error(60);
+        assert!(err.is_deadlock());
+        assert!(err.is_retryable());
+        assert!(!err.is_connection_lost());
+        assert!(!err.is_unique_violation());
+    }
+
+    #[test]
+    fn classifies_unique_violations() {
+        let err = error(1);
+        assert!(err.is_unique_violation());
+        assert!(!err.is_retryable());
+        assert!(!err.is_deadlock());
     }
+
+    #[test]
+    fn classifies_timeouts() {
+        for &code in &[12170, 3156, 51, 30006] {
+            let err = error(code);
+            assert!(err.is_timeout(), "{}", code);
+            assert!(err.is_retryable(), "{}", code);
+            assert!(!err.is_connection_lost(), "{}", code);
+        }
+    }
+
+    #[test]
+    fn classifies_transient_errors() {
+        for &code in &[12541, 12514, 8177, 54] {
+            let err = error(code);
+            assert!(err.is_retryable(), "{}", code);
+            assert!(!err.is_connection_lost() && !err.is_timeout() && !err.is_deadlock(), "{}", code);
+        }
+    }
+
+    #[test]
+    fn does_not_retry_statement_errors() {
+        for &code in &[942, 904, 1400, 1722, -1] {
+            let err = error(code);
+            assert!(!err.is_retryable(), "{}", code);
+            assert!(!err.is_connection_lost() && !err.is_timeout(), "{}", code);
+        }
+    }
+
+    #[test]
+    fn classifies_by_any_record() {
+        let mut err = error(604);
+        err.records = vec![
+            ErrorRecord { code: 604, message: "ORA-00604".to_string(), sqlstate: None },
+            ErrorRecord { code: 3113, message: "ORA-03113".to_string(), sqlstate: None },
+        ];
+        assert!(err.is_connection_lost());
+        assert!(err.is_retryable());
+        assert!(!err.is_unique_violation());
+    }
+
+    #[test]
+    fn validates_modes() {
+        for &mode in &[OCIMode::Default,
+                       OCIMode::Threaded | OCIMode::NoMutex,
+                       OCIMode::Threaded | OCIMode::Object | OCIMode::Events,
+                       OCIMode::NcharLiteralReplaceOn | OCIMode::EnableNLSValidation,
+                       OCIMode::NcharLiteralReplaceOff | OCIMode::SuppressNLSValidation] {
+            assert!(mode.validate().is_ok(), "{:?}", mode);
+        }
+        for &mode in &[OCIMode::NoMutex,
+                       OCIMode::Object | OCIMode::NoMutex,
+                       OCIMode::Threaded | OCIMode::NcharLiteralReplaceOn | OCIMode::NcharLiteralReplaceOff,
+                       OCIMode::SuppressNLSValidation | OCIMode::EnableNLSValidation] {
+            assert!(mode.validate().is_err(), "{:?}", mode);
+        }
+    }
+
+    #[test]
+    fn validates_auth_modes() {
+        for &mode in &[OCIAuthMode::Default,
+                       OCIAuthMode::Sysdba,
+                       OCIAuthMode::Sysoper | OCIAuthMode::StmtCache,
+                       OCIAuthMode::Sysdba | OCIAuthMode::PrelimAuth,
+                       OCIAuthMode::Sysoper | OCIAuthMode::PrelimAuth] {
+            assert!(mode.validate().is_ok(), "{:?}", mode);
+        }
+        for &mode in &[OCIAuthMode::Sysdba | OCIAuthMode::Sysoper,
+                       OCIAuthMode::PrelimAuth,
+                       OCIAuthMode::PrelimAuth | OCIAuthMode::StmtCache] {
+            assert!(mode.validate().is_err(), "{:?}", mode);
+        }
+    }
+
+    #[test]
+    fn validates_execute_modes() {
+        for &mode in &[OCIExecuteMode::Default,
+                       OCIExecuteMode::DescribeOnly,
+                       OCIExecuteMode::ParseOnly,
+                       OCIExecuteMode::CommitOnSuccess | OCIExecuteMode::BatchErrors] {
+            assert!(mode.validate().is_ok(), "{:?}", mode);
+        }
+        assert!((OCIExecuteMode::DescribeOnly | OCIExecuteMode::ParseOnly).validate().is_err());
+    }
+
 }
@@ -0,0 +1,439 @@
+//! Streaming access to `BLOB` and `CLOB` values
+
+use std::cmp;
+use std::io::{self, Read, Write, Seek, SeekFrom};
+use std::mem;
+use std::sync::Arc;
+use {OCILobLocator, OCIDataType, OciStatus, OracleError};
+use {oci_lob_read2, oci_lob_write2, oci_lob_get_length2, oci_lob_trim2, oci_lob_get_chunk_size};
+use charset::Encoding;
+use connection::Connection;
+use handle::Descriptor;
+use value::{self, SqlValue, ToSql};
+
+/// `BLOB` or `CLOB` read and written in place through its locator.
+///
+/// Reads and writes are buffered in pieces of the LOB chunk size, so data can be streamed with
+/// `std::io::copy` without loading the whole value into memory. Positions and lengths are in
+/// bytes for `BLOB` and in characters for `CLOB`, whose data is in the client character set
+/// of the environment.
+///
+/// Writes are flushed by [`flush`](#method.flush), seeking, reading and dropping the `Lob`.
+/// Writing requires the row to be locked, e.g. selected `FOR UPDATE`.
+pub struct Lob<'conn> {
+    stream:    Stream<OciLob<'conn>>,
+    data_type: OCIDataType,
+}
+
+impl<'conn> Lob<'conn> {
+    /// Opens the LOB of a fetched `BLOB` or `CLOB` value on `conn`.
+    pub fn new(conn: &'conn Connection, value: &SqlValue) -> Result<Lob<'conn>, OracleError> {
+        let locator = match value::locator(value) {
+            Some(locator) => locator.clone(),
+            None => return Err(OracleError::new(&format!("{:?} value has no LOB locator", value.data_type()),
+                                                "Lob::new")),
+        };
+        let chunk_size = oci_lob_get_chunk_size(conn.service_context().as_ptr(),
+                                                conn.error_handle().as_ptr(),
+                                                locator.as_ptr() as *mut OCILobLocator)?;
+        let clob = value.data_type() == OCIDataType::Clob;
+        Ok(Lob {
+            stream:    Stream::new(OciLob { conn, locator }, chunk_size as usize, clob,
+                                   conn.environment().encoding()),
+            data_type: value.data_type(),
+        })
+    }
+
+    /// Whether the LOB is a `CLOB`.
+    pub fn is_clob(&self) -> bool {
+        self.data_type == OCIDataType::Clob
+    }
+
+    /// Chunk size the reads and writes are buffered in.
+    pub fn chunk_size(&self) -> usize {
+        self.stream.chunk_size
+    }
+
+    /// Length stored in the database, pending writes are flushed first.
+    pub fn len(&mut self) -> Result<u64, OracleError> {
+        self.stream.len()
+    }
+
+    /// Whether the LOB is empty.
+    pub fn is_empty(&mut self) -> Result<bool, OracleError> {
+        self.len().map(|len| len == 0)
+    }
+
+    /// Truncates the LOB to `len`, the position is moved back if it is past the new end.
+    pub fn truncate(&mut self, len: u64) -> Result<(), OracleError> {
+        self.stream.truncate(len)
+    }
+}
+
+/// Database side of a LOB, which [`Stream`](struct.Stream.html) buffers.
+///
+/// Offsets are 1-based and, like the returned amounts, in bytes for `BLOB` and in characters
+/// for `CLOB`. Reads and writes return the number of bytes and characters transferred.
+trait Backend {
+    fn read(&mut self, offset: u64, buf: &mut [u8]) -> Result<(u64, u64), OracleError>;
+
+    fn write(&mut self, offset: u64, buf: &[u8]) -> Result<(u64, u64), OracleError>;
+
+    fn len(&mut self) -> Result<u64, OracleError>;
+
+    fn trim(&mut self, len: u64) -> Result<(), OracleError>;
+}
+
+/// LOB accessed through its locator on a connection.
+struct OciLob<'conn> {
+    conn:    &'conn Connection,
+    locator: Arc<Descriptor>,
+}
+
+impl<'conn> OciLob<'conn> {
+    fn as_ptr(&self) -> *mut OCILobLocator {
+        self.locator.as_ptr() as *mut OCILobLocator
+    }
+}
+
+impl<'conn> Backend for OciLob<'conn> {
+    fn read(&mut self, offset: u64, buf: &mut [u8]) -> Result<(u64, u64), OracleError> {
+        match oci_lob_read2(self.conn.service_context().as_ptr(), self.conn.error_handle().as_ptr(),
+                            self.as_ptr(), offset, buf) {
+            Err(ref err) if err.kind() == OciStatus::NoData => Ok((0, 0)),
+            result => result,
+        }
+    }
+
+    fn write(&mut self, offset: u64, buf: &[u8]) -> Result<(u64, u64), OracleError> {
+        oci_lob_write2(self.conn.service_context().as_ptr(), self.conn.error_handle().as_ptr(),
+                       self.as_ptr(), offset, buf)
+    }
+
+    fn len(&mut self) -> Result<u64, OracleError> {
+        oci_lob_get_length2(self.conn.service_context().as_ptr(), self.conn.error_handle().as_ptr(),
+                            self.as_ptr())
+    }
+
+    fn trim(&mut self, len: u64) -> Result<(), OracleError> {
+        oci_lob_trim2(self.conn.service_context().as_ptr(), self.conn.error_handle().as_ptr(),
+                      self.as_ptr(), len)
+    }
+}
+
+/// Read and write buffers of a LOB, in pieces of its chunk size.
+struct Stream<B> {
+    backend:    B,
+    chunk_size: usize,
+    clob:       bool,
+    encoding:   Encoding,
+    // Zero-based position of the next read or write made to the database.
+    position:   u64,
+    read_buf:   Vec<u8>,
+    read_pos:   usize,
+    write_buf:  Vec<u8>,
+}
+
+impl<B: Backend> Stream<B> {
+    fn new(backend: B, chunk_size: usize, clob: bool, encoding: Encoding) -> Stream<B> {
+        Stream {
+            backend,
+            chunk_size: cmp::max(chunk_size, 1),
+            clob,
+            encoding,
+            position:   0,
+            read_buf:   Vec::new(),
+            read_pos:   0,
+            write_buf:  Vec::new(),
+        }
+    }
+
+    fn len(&mut self) -> Result<u64, OracleError> {
+        self.write_out(true)?;
+        self.backend.len()
+    }
+
+    fn truncate(&mut self, len: u64) -> Result<(), OracleError> {
+        self.write_out(true)?;
+        let position = cmp::min(self.logical_position(), len);
+        self.discard_read();
+        self.backend.trim(len)?;
+        self.position = position;
+        Ok(())
+    }
+
+    /// Length of `bytes` in LOB units.
+    fn units(&self, bytes: &[u8]) -> u64 {
+        if self.clob {
+            bytes.iter().filter(|&&b| self.encoding.is_char_start(b)).count() as u64
+        } else {
+            bytes.len() as u64
+        }
+    }
+
+    /// Position as seen by the caller, taking the buffers into account.
+    fn logical_position(&self) -> u64 {
+        self.position - self.units(&self.read_buf[self.read_pos..]) + self.units(&self.write_buf)
+    }
+
+    /// Drops the data read ahead, moving the database position back to what the caller has read.
+    /// Pending writes are left alone, as they are written at the database position.
+    fn discard_read(&mut self) {
+        if self.read_pos < self.read_buf.len() {
+            self.position -= self.units(&self.read_buf[self.read_pos..]);
+        }
+        self.read_buf.clear();
+        self.read_pos = 0;
+    }
+
+    /// Reads from the database at the current position, returns 0 at the end of the LOB.
+    fn read_in(&mut self, buf: &mut [u8]) -> Result<usize, OracleError> {
+        let (bytes, chars) = self.backend.read(self.position + 1, buf)?;
+        self.position += if self.clob { chars } else { bytes };
+        Ok(bytes as usize)
+    }
+
+    /// Writes the buffered data, only whole chunks unless `all` is set.
+    fn write_out(&mut self, all: bool) -> Result<(), OracleError> {
+        let mut end = if all {
+            self.write_buf.len()
+        } else {
+            self.write_buf.len() / self.chunk_size * self.chunk_size
+        };
+        // A character split between chunks is kept for the next write.
+        if self.clob && !all {
+            while end > 0 && end < self.write_buf.len() && !self.encoding.is_char_start(self.write_buf[end]) {
+                end -= 1;
+            }
+        }
+        let mut start = 0;
+        let mut result = Ok(());
+        while start < end {
+            match self.backend.write(self.position + 1, &self.write_buf[start..end]) {
+                Ok((0, _)) => {
+                    result = Err(OracleError::new("No data written to LOB", "Lob::write_out"));
+                    break;
+                },
+                Ok((bytes, chars)) => {
+                    start += bytes as usize;
+                    self.position += if self.clob { chars } else { bytes };
+                },
+                Err(err) => {
+                    result = Err(err);
+                    break;
+                },
+            }
+        }
+        // Whatever was written is dropped from the buffer even if a later piece failed.
+        self.write_buf.drain(..start);
+        result
+    }
+
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.write_out(true).map_err(io_error)?;
+        if self.read_pos == self.read_buf.len() {
+            if buf.len() >= self.chunk_size {
+                return self.read_in(buf).map_err(io_error);
+            }
+            let mut read_buf = mem::take(&mut self.read_buf);
+            read_buf.resize(self.chunk_size, 0);
+            let read = self.read_in(&mut read_buf);
+            read_buf.truncate(*read.as_ref().unwrap_or(&0));
+            self.read_buf = read_buf;
+            self.read_pos = 0;
+            read.map_err(io_error)?;
+        }
+        let count = cmp::min(buf.len(), self.read_buf.len() - self.read_pos);
+        buf[..count].copy_from_slice(&self.read_buf[self.read_pos..self.read_pos + count]);
+        self.read_pos += count;
+        Ok(count)
+    }
+
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.discard_read();
+        self.write_buf.extend_from_slice(buf);
+        if self.write_buf.len() >= self.chunk_size {
+            self.write_out(false).map_err(io_error)?;
+        }
+        Ok(buf.len())
+    }
+
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        self.write_out(true).map_err(io_error)?;
+        let current = self.logical_position();
+        let position = match pos {
+            SeekFrom::Start(offset)   => offset as i128,
+            SeekFrom::End(offset)     => self.backend.len().map_err(io_error)? as i128 + offset as i128,
+            SeekFrom::Current(offset) => current as i128 + offset as i128,
+        };
+        if position < 0 || position > u64::MAX as i128 {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "Invalid LOB position"));
+        }
+        self.read_buf.clear();
+        self.read_pos = 0;
+        self.position = position as u64;
+        Ok(self.position)
+    }
+}
+
+fn io_error(err: OracleError) -> io::Error {
+    io::Error::other(err)
+}
+
+impl<'conn> Read for Lob<'conn> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.stream.read(buf)
+    }
+}
+
+impl<'conn> Write for Lob<'conn> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.stream.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.stream.write_out(true).map_err(io_error)
+    }
+}
+
+impl<'conn> Seek for Lob<'conn> {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        self.stream.seek(pos)
+    }
+}
+
+impl<'conn> Drop for Lob<'conn> {
+    fn drop(&mut self) {
+        let _ = self.stream.write_out(true);
+    }
+}
+
+/// Bound as the locator, so that the statement works on the LOB itself.
+/// Pending writes are not part of the value, [`flush`](#method.flush) the `Lob` first.
+impl<'conn> ToSql for Lob<'conn> {
+    fn to_sql(&self) -> Result<SqlValue, OracleError> {
+        Ok(value::from_locator(self.data_type, self.stream.backend.locator.clone()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{self, Read, Write, Seek, SeekFrom};
+    use OracleError;
+    use charset::Encoding;
+    use super::{Backend, Stream};
+
+    /// `BLOB` kept in memory, recording the offset and size of every write.
+    #[derive(Default)]
+    struct FakeBlob {
+        data:   Vec<u8>,
+        writes: Vec<(u64, usize)>,
+    }
+
+    impl Backend for FakeBlob {
+        fn read(&mut self, offset: u64, buf: &mut [u8]) -> Result<(u64, u64), OracleError> {
+            let start = (offset - 1) as usize;
+            if start >= self.data.len() {
+                return Ok((0, 0));
+            }
+            let count = buf.len().min(self.data.len() - start);
+            buf[..count].copy_from_slice(&self.data[start..start + count]);
+            Ok((count as u64, count as u64))
+        }
+
+        fn write(&mut self, offset: u64, buf: &[u8]) -> Result<(u64, u64), OracleError> {
+            let start = (offset - 1) as usize;
+            // Like OCI, writing past the end fills the gap with zeros.
+            if self.data.len() < start + buf.len() {
+                self.data.resize(start + buf.len(), 0);
+            }
+            self.data[start..start + buf.len()].copy_from_slice(buf);
+            self.writes.push((offset, buf.len()));
+            Ok((buf.len() as u64, buf.len() as u64))
+        }
+
+        fn len(&mut self) -> Result<u64, OracleError> {
+            Ok(self.data.len() as u64)
+        }
+
+        fn trim(&mut self, len: u64) -> Result<(), OracleError> {
+            self.data.truncate(len as usize);
+            Ok(())
+        }
+    }
+
+    fn blob(chunk_size: usize) -> Stream<FakeBlob> {
+        Stream::new(FakeBlob::default(), chunk_size, false, Encoding::Utf8)
+    }
+
+    fn sample(len: usize) -> Vec<u8> {
+        (0..len).map(|i| (i % 251) as u8).collect()
+    }
+
+    /// Checks that every write starts where the previous one ended.
+    fn assert_contiguous(writes: &[(u64, usize)]) {
+        let mut next = 1;
+        for &(offset, len) in writes {
+            assert_eq!(offset, next);
+            next += len as u64;
+        }
+    }
+
+    #[test]
+    fn small_writes_are_contiguous() {
+        let mut stream = blob(64);
+        let data = sample(10 * 1000);
+        for piece in data.chunks(10) {
+            assert_eq!(stream.write(piece).unwrap(), 10);
+        }
+        stream.write_out(true).unwrap();
+        assert_eq!(stream.backend.data, data);
+        assert_contiguous(&stream.backend.writes);
+    }
+
+    #[test]
+    fn copy_with_chunk_not_dividing_buffer() {
+        let mut stream = blob(8132);
+        let data = sample(100 * 1024);
+        struct Sink<'a>(&'a mut Stream<FakeBlob>);
+        impl<'a> Write for Sink<'a> {
+            fn write(&mut self, buf: &[u8]) -> io::Result<usize> { self.0.write(buf) }
+            fn flush(&mut self) -> io::Result<()> { Ok(()) }
+        }
+        io::copy(&mut &data[..], &mut Sink(&mut stream)).unwrap();
+        stream.write_out(true).unwrap();
+        assert_eq!(stream.backend.data, data);
+        assert_contiguous(&stream.backend.writes);
+    }
+
+    #[test]
+    fn write_after_read_overwrites_at_read_position() {
+        let mut stream = blob(16);
+        stream.backend.data = sample(40);
+        let mut buf = [0; 5];
+        assert_eq!(stream.read(&mut buf).unwrap(), 5);
+        stream.write(&[0xFF; 3]).unwrap();
+        stream.write(&[0xEE; 2]).unwrap();
+        stream.write_out(true).unwrap();
+        let mut expected = sample(40);
+        expected[5..8].copy_from_slice(&[0xFF; 3]);
+        expected[8..10].copy_from_slice(&[0xEE; 2]);
+        assert_eq!(stream.backend.data, expected);
+        assert_eq!(stream.backend.writes, vec![(6, 5)]);
+    }
+
+    #[test]
+    fn seek_flushes_and_moves() {
+        let mut stream = blob(16);
+        stream.write(&sample(20)).unwrap();
+        assert_eq!(stream.seek(SeekFrom::Current(0)).unwrap(), 20);
+        assert_eq!(stream.seek(SeekFrom::Start(4)).unwrap(), 4);
+        let mut buf = Vec::new();
+        struct Source<'a>(&'a mut Stream<FakeBlob>);
+        impl<'a> Read for Source<'a> {
+            fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> { self.0.read(buf) }
+        }
+        Source(&mut stream).read_to_end(&mut buf).unwrap();
+        assert_eq!(buf, &sample(20)[4..]);
+    }
+}
@@ -0,0 +1,403 @@
+//! Oracle `NUMBER` in its `OCINumber` wire format
+
+use std::convert::TryFrom;
+use std::fmt;
+use std::str::FromStr;
+use {OCIDataType, OracleError};
+use value::{SqlValue, ToSql, FromSql};
+
+/// Size of `OCINumber`: a length byte followed by up to 21 bytes of the number.
+const NUMBER_SIZE: usize = 22;
+
+/// Maximum number of base-100 mantissa digits.
+const MAX_DIGITS: usize = 20;
+
+/// Exact Oracle `NUMBER` kept in the 22-byte `OCINumber` format (`SQLT_VNU`).
+///
+/// The first byte is the length of the rest. Then comes the exponent byte and up to
+/// 20 base-100 mantissa digits:
+///
+/// * zero is the single byte `0x80`;
+/// * a positive number has the exponent byte `193 + e` and digits stored as `d + 1`;
+/// * a negative number has the exponent byte `62 - e`, digits stored as `101 - d`
+///   and, if it has less than 20 digits, a trailing `102`,
+///
+/// where the value is `d0 * 100^e + d1 * 100^(e-1) + ...`.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct OracleNumber {
+    bytes: [u8; NUMBER_SIZE],
+}
+
+/// Sign, base-100 digits without leading and trailing zeros and exponent of the first digit.
+struct Decoded {
+    negative: bool,
+    digits:   Vec<u8>,
+    exponent: i32,
+}
+
+impl OracleNumber {
+    /// Zero.
+    pub fn zero() -> OracleNumber {
+        let mut bytes = [0; NUMBER_SIZE];
+        bytes[0] = 1;
+        bytes[1] = 0x80;
+        OracleNumber { bytes }
+    }
+
+    /// Reads a number in `OCINumber` format, trailing bytes after the length are ignored.
+    pub fn from_bytes(bytes: &[u8]) -> Result<OracleNumber, OracleError> {
+        let length = match bytes.first() {
+            Some(&length) if length >= 1 && (length as usize) < NUMBER_SIZE
+                                         && (length as usize) < bytes.len() => length as usize,
+            _ => return Err(OracleError::new("Invalid OCINumber length", "OracleNumber::from_bytes")),
+        };
+        let mut number = OracleNumber { bytes: [0; NUMBER_SIZE] };
+        number.bytes[..length + 1].copy_from_slice(&bytes[..length + 1]);
+        Ok(number)
+    }
+
+    /// Number in `OCINumber` format, the length byte followed by the number.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes[..self.bytes[0] as usize + 1]
+    }
+
+    /// Converts an integral number to `i128`.
+    pub fn to_i128(&self) -> Result<i128, OracleError> {
+        self.to_string().parse().map_err(|_| {
+            OracleError::new(&format!("{} is not representable as i128", self), "OracleNumber::to_i128")
+        })
+    }
+
+    /// Converts an integral non-negative number to `u128`.
+    pub fn to_u128(&self) -> Result<u128, OracleError> {
+        self.to_string().parse().map_err(|_| {
+            OracleError::new(&format!("{} is not representable as u128", self), "OracleNumber::to_u128")
+        })
+    }
+
+    /// Converts the number to the nearest `f64`.
+    pub fn to_f64(&self) -> f64 {
+        match self.to_string().as_str() {
+            "~"  => f64::INFINITY,
+            "-~" => f64::NEG_INFINITY,
+            text => text.parse().unwrap_or(f64::NAN),
+        }
+    }
+
+    /// Converts a finite `f64` using its shortest exact decimal representation.
+    pub fn from_f64(number: f64) -> Result<OracleNumber, OracleError> {
+        if !number.is_finite() {
+            return Err(OracleError::new(&format!("{} is not a finite number", number),
+                                        "OracleNumber::from_f64"));
+        }
+        number.to_string().parse()
+    }
+
+    fn decode(&self) -> Option<Decoded> {
+        let body = &self.as_bytes()[1..];
+        if body == [0x80] {
+            return Some(Decoded { negative: false, digits: Vec::new(), exponent: 0 });
+        }
+        if body[0] & 0x80 != 0 {
+            if body == [0xFF, 0x65] {
+                return None;
+            }
+            Some(Decoded {
+                negative: false,
+                digits:   body[1..].iter().map(|&d| d.wrapping_sub(1)).collect(),
+                exponent: body[0] as i32 - 193,
+            })
+        } else {
+            if body == [0x00] {
+                return None;
+            }
+            let digits = match body.last() {
+                Some(&102) => &body[1..body.len() - 1],
+                _          => &body[1..],
+            };
+            Some(Decoded {
+                negative: true,
+                digits:   digits.iter().map(|&d| 101u8.wrapping_sub(d)).collect(),
+                exponent: 62 - body[0] as i32,
+            })
+        }
+    }
+
+    fn encode(decoded: &Decoded) -> Result<OracleNumber, OracleError> {
+        if decoded.digits.is_empty() {
+            return Ok(OracleNumber::zero());
+        }
+        if decoded.digits.len() > MAX_DIGITS || decoded.exponent < -65 || decoded.exponent > 62 {
+            return Err(out_of_range());
+        }
+        let mut body = Vec::with_capacity(NUMBER_SIZE - 1);
+        if decoded.negative {
+            body.push((62 - decoded.exponent) as u8);
+            body.extend(decoded.digits.iter().map(|&d| 101 - d));
+            if decoded.digits.len() < MAX_DIGITS {
+                body.push(102);
+            }
+        } else {
+            body.push((193 + decoded.exponent) as u8);
+            body.extend(decoded.digits.iter().map(|&d| d + 1));
+        }
+        let mut number = OracleNumber { bytes: [0; NUMBER_SIZE] };
+        number.bytes[0] = body.len() as u8;
+        number.bytes[1..body.len() + 1].copy_from_slice(&body);
+        Ok(number)
+    }
+}
+
+impl FromStr for OracleNumber {
+    type Err = OracleError;
+
+    /// Parses a decimal number like `-123.45` or `1.5E-10`.
+    fn from_str(text: &str) -> Result<OracleNumber, OracleError> {
+        let error = || OracleError::new(&format!("Invalid number {:?}", text), "OracleNumber::from_str");
+        let text = text.trim();
+        let (negative, text) = match text.as_bytes().first() {
+            Some(&b'-') => (true, &text[1..]),
+            Some(&b'+') => (false, &text[1..]),
+            _           => (false, text),
+        };
+        let (mantissa, exponent) = match text.find(['e', 'E']) {
+            Some(pos) => (&text[..pos], text[pos + 1..].parse::<i32>().map_err(|_| error())?),
+            None      => (text, 0),
+        };
+        let (integer, fraction) = match mantissa.find('.') {
+            Some(pos) => (&mantissa[..pos], &mantissa[pos + 1..]),
+            None      => (mantissa, ""),
+        };
+        if integer.is_empty() && fraction.is_empty() {
+            return Err(error());
+        }
+        let mut digits = Vec::with_capacity(integer.len() + fraction.len() + 1);
+        for c in integer.bytes().chain(fraction.bytes()) {
+            if !c.is_ascii_digit() {
+                return Err(error());
+            }
+            digits.push(c - b'0');
+        }
+        // The value is 0.<digits> * 10^point, which is only out of range if `point` overflows.
+        let leading = digits.iter().take_while(|&&d| d == 0).count();
+        digits.drain(..leading);
+        while digits.last() == Some(&0) {
+            digits.pop();
+        }
+        if digits.is_empty() {
+            return Ok(OracleNumber::zero());
+        }
+        let mut point = i32::try_from(integer.len()).ok()
+            .and_then(|len| len.checked_add(exponent))
+            .and_then(|point| i32::try_from(leading).ok().and_then(|leading| point.checked_sub(leading)))
+            .ok_or_else(out_of_range)?;
+        // Align the digits to base-100 pairs around the decimal point.
+        if point % 2 != 0 {
+            digits.insert(0, 0);
+            point = point.checked_add(1).ok_or_else(out_of_range)?;
+        }
+        if digits.len() % 2 != 0 {
+            digits.push(0);
+        }
+        OracleNumber::encode(&Decoded {
+            negative,
+            digits:   digits.chunks(2).map(|pair| pair[0] * 10 + pair[1]).collect(),
+            exponent: point / 2 - 1,
+        })
+    }
+}
+
+fn out_of_range() -> OracleError {
+    OracleError::new("Number is out of range of Oracle NUMBER", "OracleNumber::encode")
+}
+
+impl fmt::Display for OracleNumber {
+    /// Formats the number in plain decimal notation, infinities as `~` and `-~` like Oracle.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let decoded = match self.decode() {
+            Some(decoded) => decoded,
+            None          => return f.write_str(if self.bytes[1] == 0 { "-~" } else { "~" }),
+        };
+        if decoded.digits.is_empty() {
+            return f.write_str("0");
+        }
+        let mut digits: Vec<u8> = decoded.digits.iter()
+            .flat_map(|&d| vec![b'0' + d / 10, b'0' + d % 10])
+            .collect();
+        let mut point = 2 * (decoded.exponent + 1);
+        if digits[0] == b'0' {
+            digits.remove(0);
+            point -= 1;
+        }
+        while digits.last() == Some(&b'0') {
+            digits.pop();
+        }
+        let digits = String::from_utf8(digits).unwrap_or_default();
+        let sign = if decoded.negative { "-" } else { "" };
+        if point <= 0 {
+            write!(f, "{}0.{}{}", sign, "0".repeat(-point as usize), digits)
+        } else if point as usize >= digits.len() {
+            write!(f, "{}{}{}", sign, digits, "0".repeat(point as usize - digits.len()))
+        } else {
+            let (integer, fraction) = digits.split_at(point as usize);
+            write!(f, "{}{}.{}", sign, integer, fraction)
+        }
+    }
+}
+
+impl fmt::Debug for OracleNumber {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "OracleNumber({})", self)
+    }
+}
+
+impl From<i128> for OracleNumber {
+    fn from(number: i128) -> OracleNumber {
+        // Every i128 has at most 39 digits and always fits.
+        number.to_string().parse().unwrap_or_else(|_| OracleNumber::zero())
+    }
+}
+
+impl From<u128> for OracleNumber {
+    fn from(number: u128) -> OracleNumber {
+        // Every u128 has at most 39 digits and always fits.
+        number.to_string().parse().unwrap_or_else(|_| OracleNumber::zero())
+    }
+}
+
+/// Bound as `SQLT_VNU`, the bytes are passed through unchanged.
+impl ToSql for OracleNumber {
+    fn to_sql(&self) -> Result<SqlValue, OracleError> {
+        Ok(SqlValue::new(OCIDataType::NumericWithLength, self.bytes.to_vec()))
+    }
+}
+
+impl FromSql for OracleNumber {
+    fn from_sql(value: &SqlValue) -> Result<OracleNumber, OracleError> {
+        if value.is_null() {
+            return Err(OracleError::new("Cannot convert NULL to OracleNumber", "FromSql::from_sql"));
+        }
+        match value.data_type() {
+            OCIDataType::NumericWithLength => OracleNumber::from_bytes(value.as_bytes()),
+            OCIDataType::Float             => OracleNumber::from_f64(f64::from_sql(value)?),
+            OCIDataType::Int | OCIDataType::Uint => i128::from_sql(value).map(OracleNumber::from),
+            _                              => String::from_sql(value)?.parse(),
+        }
+    }
+}
+
+impl ToSql for i128 {
+    fn to_sql(&self) -> Result<SqlValue, OracleError> {
+        OracleNumber::from(*self).to_sql()
+    }
+}
+
+impl ToSql for u128 {
+    fn to_sql(&self) -> Result<SqlValue, OracleError> {
+        OracleNumber::from(*self).to_sql()
+    }
+}
+
+impl FromSql for u128 {
+    fn from_sql(value: &SqlValue) -> Result<u128, OracleError> {
+        OracleNumber::from_sql(value)?.to_u128()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::OracleNumber;
+
+    fn number(text: &str) -> OracleNumber {
+        text.parse().unwrap()
+    }
+
+    #[test]
+    fn round_trips_integer_limits() {
+        for &value in &[i128::MIN, i128::MAX, -1, 0, 1] {
+            let number = OracleNumber::from(value);
+            assert_eq!(number.to_i128().unwrap(), value);
+            assert_eq!(number.to_string(), value.to_string());
+        }
+        for &value in &[u128::MIN, u128::MAX] {
+            assert_eq!(OracleNumber::from(value).to_u128().unwrap(), value);
+        }
+        assert!(OracleNumber::from(-1i128).to_u128().is_err());
+        assert!(OracleNumber::from(u128::MAX).to_i128().is_err());
+    }
+
+    #[test]
+    fn encodes_zero() {
+        for text in &["0", "-0", "0.000", "0e10", ".0"] {
+            assert_eq!(number(text).as_bytes(), &[1, 0x80]);
+        }
+        assert_eq!(OracleNumber::zero().to_string(), "0");
+    }
+
+    #[test]
+    fn encodes_negative_numbers() {
+        // Fewer than 20 digits end with 102.
+        assert_eq!(number("-1").as_bytes(), &[3, 62, 100, 102]);
+        assert_eq!(number("-123.45").as_bytes(), &[5, 61, 100, 78, 56, 102]);
+        // 20 digits have no trailing 102.
+        let twenty = "-1234567890123456789012345678901234567891";
+        let bytes = number(twenty).as_bytes().to_vec();
+        assert_eq!(bytes.len(), 22);
+        assert_ne!(bytes[21], 102);
+        assert_eq!(OracleNumber::from_bytes(&bytes).unwrap().to_string(), twenty);
+        assert_eq!(number("-123.45").to_string(), "-123.45");
+    }
+
+    #[test]
+    fn encodes_positive_numbers() {
+        assert_eq!(number("1").as_bytes(), &[2, 193, 2]);
+        assert_eq!(number("123.45").as_bytes(), &[4, 194, 2, 24, 46]);
+        assert_eq!(number("+0.001").to_string(), "0.001");
+    }
+
+    #[test]
+    fn decodes_infinity() {
+        let positive = OracleNumber::from_bytes(&[2, 0xFF, 0x65]).unwrap();
+        assert_eq!(positive.to_string(), "~");
+        assert_eq!(positive.to_f64(), f64::INFINITY);
+        let negative = OracleNumber::from_bytes(&[1, 0x00]).unwrap();
+        assert_eq!(negative.to_string(), "-~");
+        assert_eq!(negative.to_f64(), f64::NEG_INFINITY);
+    }
+
+    #[test]
+    fn handles_exponents() {
+        assert_eq!(number("1.5E-10").to_string(), "0.00000000015");
+        assert_eq!(number("1e125").to_string(), format!("1{}", "0".repeat(125)));
+        assert_eq!(number("1e-130").to_string(), format!("0.{}1", "0".repeat(129)));
+        assert!("1e126".parse::<OracleNumber>().is_err());
+        assert!("1e-131".parse::<OracleNumber>().is_err());
+    }
+
+    #[test]
+    fn rejects_overflowing_exponents() {
+        for text in &["1e2147483647", "0.01e-2147483648", "9e2147483646", "1e-2147483648"] {
+            assert!(text.parse::<OracleNumber>().is_err(), "{}", text);
+        }
+        assert_eq!(number("0e2147483647").to_string(), "0");
+    }
+
+    #[test]
+    fn rejects_invalid_strings() {
+        for text in &["", "-", ".", "e5", "1e", "1.2.3", "12a", "1e1.5", "--1", "~", " - 1"] {
+            assert!(text.parse::<OracleNumber>().is_err(), "{:?}", text);
+        }
+    }
+
+    #[test]
+    fn rejects_too_many_digits() {
+        assert!("1234567890123456789012345678901234567890123".parse::<OracleNumber>().is_err());
+    }
+
+    #[test]
+    fn rejects_invalid_lengths() {
+        assert!(OracleNumber::from_bytes(&[]).is_err());
+        assert!(OracleNumber::from_bytes(&[0]).is_err());
+        assert!(OracleNumber::from_bytes(&[3, 193]).is_err());
+    }
+}
@@ -0,0 +1,491 @@
+//! Connection pooling in Rust, for when OCI pooling is not available
+
+use std::collections::VecDeque;
+use std::mem;
+use std::ops::{Deref, DerefMut};
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+use OracleError;
+use connection::Connection;
+use handle::Environment;
+
+/// Opens and checks the connections of a [`Pool`](struct.Pool.html).
+///
+/// [`OracleConnectionFactory`](struct.OracleConnectionFactory.html) logs in to a database,
+/// any other implementation can stand in for it, e.g. to use the pool without a database.
+pub trait ConnectionFactory: Send + Sync + 'static {
+    /// Connection handed out by the pool.
+    type Connection: Send + 'static;
+
+    /// Opens a new connection.
+    fn connect(&self) -> Result<Self::Connection, OracleError>;
+
+    /// Checks that an idle connection is still usable before it is handed out again.
+    fn is_valid(&self, conn: &Self::Connection) -> Result<(), OracleError>;
+}
+
+/// Opens connections with [`Connection::connect_with_env`](struct.Connection.html#method.connect_with_env),
+/// which attaches a server and begins a session, and checks them with
+/// [`Connection::ping`](struct.Connection.html#method.ping).
+#[derive(Clone)]
+pub struct OracleConnectionFactory {
+    env:            Environment,
+    username:       String,
+    password:       String,
+    connect_string: String,
+}
+
+impl OracleConnectionFactory {
+    /// Factory of connections for `username` to `connect_string` in `env`.
+    pub fn new(env: &Environment, username: &str, password: &str, connect_string: &str) -> OracleConnectionFactory {
+        OracleConnectionFactory {
+            env:            env.clone(),
+            username:       username.to_string(),
+            password:       password.to_string(),
+            connect_string: connect_string.to_string(),
+        }
+    }
+}
+
+impl ConnectionFactory for OracleConnectionFactory {
+    type Connection = Connection;
+
+    fn connect(&self) -> Result<Connection, OracleError> {
+        Connection::connect_with_env(&self.env, &self.username, &self.password, &self.connect_string)
+    }
+
+    fn is_valid(&self, conn: &Connection) -> Result<(), OracleError> {
+        conn.ping()
+    }
+}
+
+/// Snapshot of the usage of a [`Pool`](struct.Pool.html).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PoolMetrics {
+    /// Connections handed out or being opened.
+    pub in_use:            usize,
+
+    /// Open connections waiting in the pool.
+    pub idle:              usize,
+
+    /// Threads waiting for a connection.
+    pub waiters:           usize,
+
+    /// Connections that failed to open since the pool was created.
+    pub creation_failures: u64,
+}
+
+struct Idle<C> {
+    conn:      C,
+    created:   Instant,
+    last_used: Instant,
+}
+
+struct State<C> {
+    idle:              VecDeque<Idle<C>>,
+    in_use:            usize,
+    waiters:           usize,
+    creation_failures: u64,
+}
+
+struct Shared<F: ConnectionFactory> {
+    factory:      F,
+    max_size:     usize,
+    wait_timeout: Duration,
+    idle_timeout: Option<Duration>,
+    max_lifetime: Option<Duration>,
+    state:        Mutex<State<F::Connection>>,
+    available:    Condvar,
+}
+
+impl<F: ConnectionFactory> Shared<F> {
+    fn lock(&self) -> MutexGuard<'_, State<F::Connection>> {
+        self.state.lock().unwrap_or_else(|err| err.into_inner())
+    }
+
+    fn expired(&self, created: Instant) -> bool {
+        self.max_lifetime.is_some_and(|lifetime| created.elapsed() >= lifetime)
+    }
+
+    /// Removes the idle connections past their idle timeout or lifetime, which the caller closes
+    /// once the lock is released.
+    fn evict(&self, state: &mut State<F::Connection>) -> Vec<F::Connection> {
+        let (kept, evicted) = mem::take(&mut state.idle).into_iter().partition::<VecDeque<_>, _>(|idle| {
+            !self.expired(idle.created)
+            && self.idle_timeout.is_none_or(|timeout| idle.last_used.elapsed() < timeout)
+        });
+        state.idle = kept;
+        evicted.into_iter().map(|idle| idle.conn).collect()
+    }
+}
+
+/// Pool of connections opened by a [`ConnectionFactory`](trait.ConnectionFactory.html).
+///
+/// Idle connections are checked with `ConnectionFactory::is_valid` when taken from the pool and
+/// closed once idle for longer than the idle timeout or older than the maximum lifetime.
+/// Created with a [`PoolBuilder`](struct.PoolBuilder.html), cloning is cheap and shares the same pool.
+pub struct Pool<F: ConnectionFactory> {
+    shared: Arc<Shared<F>>,
+}
+
+impl<F: ConnectionFactory> Clone for Pool<F> {
+    fn clone(&self) -> Pool<F> {
+        Pool { shared: self.shared.clone() }
+    }
+}
+
+impl<F: ConnectionFactory> Pool<F> {
+    /// Takes a valid idle connection or opens a new one.
+    ///
+    /// When the pool is at its maximum size, waits for a connection to be given back and fails
+    /// after the wait timeout. A connection failing the check is closed and the next one is tried.
+    pub fn get(&self) -> Result<PooledConnection<F>, OracleError> {
+        let shared = &*self.shared;
+        let deadline = Instant::now() + shared.wait_timeout;
+        let mut state = shared.lock();
+        loop {
+            let evicted = shared.evict(&mut state);
+            if !evicted.is_empty() {
+                drop(state);
+                drop(evicted);
+                state = shared.lock();
+                continue;
+            }
+            if let Some(idle) = state.idle.pop_front() {
+                state.in_use += 1;
+                drop(state);
+                if shared.factory.is_valid(&idle.conn).is_ok() {
+                    return Ok(PooledConnection { conn: Some(idle.conn), created: idle.created, pool: self.clone() });
+                }
+                drop(idle);
+                state = shared.lock();
+                state.in_use -= 1;
+                continue;
+            }
+            if state.in_use < shared.max_size {
+                // The slot is taken before connecting so that the pool cannot grow past its maximum.
+                state.in_use += 1;
+                drop(state);
+                return match shared.factory.connect() {
+                    Ok(conn) => Ok(PooledConnection { conn: Some(conn), created: Instant::now(), pool: self.clone() }),
+                    Err(err) => {
+                        let mut state = shared.lock();
+                        state.in_use -= 1;
+                        state.creation_failures += 1;
+                        shared.available.notify_one();
+                        Err(err)
+                    },
+                };
+            }
+            let now = Instant::now();
+            if now >= deadline {
+                return Err(OracleError::new(&format!("Timed out after {:?} waiting for a pooled connection",
+                                                     shared.wait_timeout),
+                                            "Pool::get"));
+            }
+            state.waiters += 1;
+            state = shared.available.wait_timeout(state, deadline - now)
+                          .unwrap_or_else(|err| err.into_inner()).0;
+            state.waiters -= 1;
+        }
+    }
+
+    /// Current usage of the pool.
+    pub fn metrics(&self) -> PoolMetrics {
+        let state = self.shared.lock();
+        PoolMetrics {
+            in_use:            state.in_use,
+            idle:              state.idle.len(),
+            waiters:           state.waiters,
+            creation_failures: state.creation_failures,
+        }
+    }
+
+    /// Closes the idle connections past their idle timeout or lifetime without waiting for the
+    /// next [`get`](#method.get).
+    pub fn evict_idle(&self) {
+        let evicted = {
+            let mut state = self.shared.lock();
+            self.shared.evict(&mut state)
+        };
+        drop(evicted);
+    }
+
+    /// Factory the pool opens connections with.
+    pub fn factory(&self) -> &F {
+        &self.shared.factory
+    }
+
+    fn put_back(&self, conn: F::Connection, created: Instant) {
+        let shared = &*self.shared;
+        let mut state = shared.lock();
+        state.in_use -= 1;
+        let closed = if shared.expired(created) {
+            Some(conn)
+        } else {
+            state.idle.push_back(Idle { conn, created, last_used: Instant::now() });
+            None
+        };
+        shared.available.notify_one();
+        drop(state);
+        drop(closed);
+    }
+}
+
+/// Configuration of a new [`Pool`](struct.Pool.html).
+#[derive(Clone, Debug)]
+pub struct PoolBuilder {
+    max_size:     usize,
+    wait_timeout: Duration,
+    idle_timeout: Option<Duration>,
+    max_lifetime: Option<Duration>,
+}
+
+impl Default for PoolBuilder {
+    fn default() -> PoolBuilder {
+        PoolBuilder {
+            max_size:     10,
+            wait_timeout: Duration::from_secs(30),
+            idle_timeout: Some(Duration::from_secs(10 * 60)),
+            max_lifetime: Some(Duration::from_secs(30 * 60)),
+        }
+    }
+}
+
+impl PoolBuilder {
+    /// Starts configuring a pool with the default settings.
+    pub fn new() -> PoolBuilder {
+        PoolBuilder::default()
+    }
+
+    /// Maximum number of open connections, 10 by default.
+    pub fn max_size(mut self, max_size: usize) -> PoolBuilder {
+        self.max_size = max_size;
+        self
+    }
+
+    /// Time [`Pool::get`](struct.Pool.html#method.get) waits for a connection, 30 seconds by default.
+    pub fn wait_timeout(mut self, timeout: Duration) -> PoolBuilder {
+        self.wait_timeout = timeout;
+        self
+    }
+
+    /// Closes connections idle for longer than `timeout`, 10 minutes by default.
+    /// `None` keeps them.
+    pub fn idle_timeout(mut self, timeout: Option<Duration>) -> PoolBuilder {
+        self.idle_timeout = timeout;
+        self
+    }
+
+    /// Closes connections older than `lifetime` once given back, 30 minutes by default.
+    /// `None` keeps them.
+    pub fn max_lifetime(mut self, lifetime: Option<Duration>) -> PoolBuilder {
+        self.max_lifetime = lifetime;
+        self
+    }
+
+    /// Creates an empty pool of connections opened by `factory`.
+    pub fn build<F: ConnectionFactory>(self, factory: F) -> Result<Pool<F>, OracleError> {
+        if self.max_size == 0 {
+            return Err(OracleError::new("Pool size must be at least 1", "PoolBuilder::build"));
+        }
+        Ok(Pool {
+            shared: Arc::new(Shared {
+                factory,
+                max_size:     self.max_size,
+                wait_timeout: self.wait_timeout,
+                idle_timeout: self.idle_timeout,
+                max_lifetime: self.max_lifetime,
+                state:        Mutex::new(State {
+                    idle:              VecDeque::new(),
+                    in_use:            0,
+                    waiters:           0,
+                    creation_failures: 0,
+                }),
+                available:    Condvar::new(),
+            }),
+        })
+    }
+}
+
+/// Connection taken from a [`Pool`](struct.Pool.html), given back to it on drop.
+pub struct PooledConnection<F: ConnectionFactory> {
+    conn:    Option<F::Connection>,
+    created: Instant,
+    pool:    Pool<F>,
+}
+
+impl<F: ConnectionFactory> PooledConnection<F> {
+    /// Closes the connection rather than giving it back, e.g. after an error leaving it unusable.
+    pub fn discard(mut self) {
+        let conn = self.conn.take();
+        {
+            let mut state = self.pool.shared.lock();
+            state.in_use -= 1;
+            self.pool.shared.available.notify_one();
+        }
+        drop(conn);
+    }
+}
+
+impl<F: ConnectionFactory> Deref for PooledConnection<F> {
+    type Target = F::Connection;
+
+    fn deref(&self) -> &F::Connection {
+        self.conn.as_ref().unwrap()
+    }
+}
+
+impl<F: ConnectionFactory> DerefMut for PooledConnection<F> {
+    fn deref_mut(&mut self) -> &mut F::Connection {
+        self.conn.as_mut().unwrap()
+    }
+}
+
+impl<F: ConnectionFactory> Drop for PooledConnection<F> {
+    fn drop(&mut self) {
+        if let Some(conn) = self.conn.take() {
+            self.pool.put_back(conn, self.created);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+    use std::thread;
+    use std::time::{Duration, Instant};
+    use OracleError;
+    use super::{ConnectionFactory, Pool, PoolBuilder, PoolMetrics};
+
+    /// Connections numbered in the order they are opened.
+    #[derive(Default)]
+    struct FakeFactory {
+        opened:  AtomicUsize,
+        fail:    AtomicBool,
+        // Connections failing the check.
+        invalid: Mutex<Vec<usize>>,
+    }
+
+    impl ConnectionFactory for FakeFactory {
+        type Connection = usize;
+
+        fn connect(&self) -> Result<usize, OracleError> {
+            if self.fail.load(Ordering::SeqCst) {
+                return Err(OracleError::new("Connection refused", "FakeFactory::connect"));
+            }
+            Ok(self.opened.fetch_add(1, Ordering::SeqCst) + 1)
+        }
+
+        fn is_valid(&self, conn: &usize) -> Result<(), OracleError> {
+            if self.invalid.lock().unwrap().contains(conn) {
+                Err(OracleError::new("Connection lost", "FakeFactory::is_valid"))
+            } else {
+                Ok(())
+            }
+        }
+    }
+
+    fn pool(builder: PoolBuilder) -> Pool<FakeFactory> {
+        builder.build(FakeFactory::default()).unwrap()
+    }
+
+    fn metrics(in_use: usize, idle: usize, creation_failures: u64) -> PoolMetrics {
+        PoolMetrics { in_use, idle, waiters: 0, creation_failures }
+    }
+
+    #[test]
+    fn reuses_idle_connections() {
+        let pool = pool(PoolBuilder::new());
+        let conn = pool.get().unwrap();
+        assert_eq!(*conn, 1);
+        assert_eq!(pool.metrics(), metrics(1, 0, 0));
+        drop(conn);
+        assert_eq!(pool.metrics(), metrics(0, 1, 0));
+        assert_eq!(*pool.get().unwrap(), 1);
+        assert_eq!(pool.factory().opened.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn replaces_connections_failing_the_check() {
+        let pool = pool(PoolBuilder::new());
+        drop((pool.get().unwrap(), pool.get().unwrap()));
+        assert_eq!(pool.metrics(), metrics(0, 2, 0));
+        pool.factory().invalid.lock().unwrap().extend(&[1, 2]);
+        let conn = pool.get().unwrap();
+        assert_eq!(*conn, 3);
+        assert_eq!(pool.metrics(), metrics(1, 0, 0));
+    }
+
+    #[test]
+    fn evicts_connections_idle_too_long() {
+        let pool = pool(PoolBuilder::new().idle_timeout(Some(Duration::from_millis(20))));
+        drop(pool.get().unwrap());
+        pool.evict_idle();
+        assert_eq!(pool.metrics(), metrics(0, 1, 0));
+        thread::sleep(Duration::from_millis(40));
+        pool.evict_idle();
+        assert_eq!(pool.metrics(), metrics(0, 0, 0));
+        assert_eq!(*pool.get().unwrap(), 2);
+    }
+
+    #[test]
+    fn evicts_connections_past_their_lifetime() {
+        let pool = pool(PoolBuilder::new().idle_timeout(None).max_lifetime(Some(Duration::from_millis(20))));
+        let conn = pool.get().unwrap();
+        drop(pool.get().unwrap());
+        thread::sleep(Duration::from_millis(40));
+        // The idle connection is evicted on the next get, the other one when given back.
+        assert_eq!(*pool.get().unwrap(), 3);
+        drop(conn);
+        assert_eq!(pool.metrics(), metrics(0, 1, 0));
+        assert_eq!(*pool.get().unwrap(), 3);
+    }
+
+    #[test]
+    fn times_out_when_full() {
+        let pool = pool(PoolBuilder::new().max_size(1).wait_timeout(Duration::from_millis(50)));
+        let conn = pool.get().unwrap();
+        let start = Instant::now();
+        assert!(pool.get().is_err());
+        assert!(start.elapsed() >= Duration::from_millis(50));
+        drop(conn);
+        assert_eq!(*pool.get().unwrap(), 1);
+    }
+
+    #[test]
+    fn waits_for_a_connection_given_back() {
+        let pool = pool(PoolBuilder::new().max_size(1).wait_timeout(Duration::from_secs(5)));
+        let conn = pool.get().unwrap();
+        let waiter = {
+            let pool = pool.clone();
+            thread::spawn(move || *pool.get().unwrap())
+        };
+        while pool.metrics().waiters == 0 {
+            thread::sleep(Duration::from_millis(1));
+        }
+        assert_eq!(pool.metrics(), PoolMetrics { in_use: 1, idle: 0, waiters: 1, creation_failures: 0 });
+        drop(conn);
+        assert_eq!(waiter.join().unwrap(), 1);
+        assert_eq!(pool.metrics(), metrics(0, 1, 0));
+    }
+
+    #[test]
+    fn counts_creation_failures() {
+        let pool = pool(PoolBuilder::new().max_size(1));
+        pool.factory().fail.store(true, Ordering::SeqCst);
+        assert!(pool.get().is_err());
+        assert!(pool.get().is_err());
+        assert_eq!(pool.metrics(), metrics(0, 0, 2));
+        pool.factory().fail.store(false, Ordering::SeqCst);
+        let conn = pool.get().unwrap();
+        conn.discard();
+        assert_eq!(pool.metrics(), metrics(0, 0, 2));
+    }
+
+    #[test]
+    fn rejects_empty_pool() {
+        assert!(PoolBuilder::new().max_size(0).build(FakeFactory::default()).is_err());
+    }
+}
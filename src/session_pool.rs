@@ -0,0 +1,296 @@
+//! Session pooling
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use {c_void, c_uchar, c_uint, OCISPool, OCISvcCtx, OCIHandleType, OCIAttribute, OCISessionReleaseMode,
+     OracleError};
+use {oci_handle_alloc, oci_handle_free, oci_attr_set, oci_handle_attr_get, oci_session_pool_create,
+     oci_session_pool_destroy, oci_session_get, oci_session_release, oci_ping};
+use connection::{self, Connection};
+use handle::{Environment, ErrorHandle, ServiceContext};
+
+/// What [`SessionPool::get`](struct.SessionPool.html#method.get) does when every session is busy
+/// and the pool is at its maximum.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PoolGetMode {
+    /// `OCI_SPOOL_ATTRVAL_WAIT`. Waits for a session to be given back.
+    Wait      = 0,
+
+    /// `OCI_SPOOL_ATTRVAL_NOWAIT`. Fails with `ORA-24496`.
+    NoWait    = 1,
+
+    /// `OCI_SPOOL_ATTRVAL_FORCEGET`. Opens a session beyond the maximum.
+    ForcedGet = 2,
+}
+
+struct SessionPoolHandle {
+    handle:        *mut OCISPool,
+    name:          Vec<u8>,
+    created:       bool,
+    max:           u32,
+    wait_timeout:  Option<Duration>,
+    ping_interval: Option<Duration>,
+    // When each idle session was given back, keyed by its service context.
+    released:      Mutex<HashMap<usize, Instant>>,
+    error:         Mutex<ErrorHandle>,
+    env:           Environment,
+}
+
+impl Drop for SessionPoolHandle {
+    fn drop(&mut self) {
+        if self.created {
+            let error = self.error.lock().unwrap_or_else(|err| err.into_inner());
+            let _ = oci_session_pool_destroy(self.handle, error.as_ptr());
+        }
+        let _ = oci_handle_free(self.handle as *mut c_void, OCIHandleType::SessionPool);
+    }
+}
+
+// The pool is created in a threaded environment, which makes OCI serialise the calls on it.
+unsafe impl Send for SessionPoolHandle {}
+unsafe impl Sync for SessionPoolHandle {}
+
+/// Pool of sessions created with [`oci_session_pool_create`](fn.oci_session_pool_create.html).
+///
+/// Cloning is cheap and shares the same pool, which is destroyed once the last clone and every
+/// connection taken from it are dropped.
+#[derive(Clone)]
+pub struct SessionPool {
+    inner: Arc<SessionPoolHandle>,
+}
+
+impl SessionPool {
+    /// Starts configuring a pool of sessions for `username` on `connect_string`.
+    pub fn builder(username: &str, password: &str, connect_string: &str) -> SessionPoolBuilder {
+        SessionPoolBuilder {
+            username:       username.to_string(),
+            password:       password.to_string(),
+            connect_string: connect_string.to_string(),
+            env:            None,
+            min:            1,
+            max:            10,
+            increment:      1,
+            get_mode:       PoolGetMode::Wait,
+            timeout:        None,
+            wait_timeout:   None,
+            ping_interval:  Some(Duration::from_secs(60)),
+        }
+    }
+
+    /// Takes a session from the pool with [`oci_session_get`](fn.oci_session_get.html).
+    ///
+    /// A session idle for longer than the ping interval is pinged first and dropped from the pool
+    /// if it is unusable. The ping error is returned once as many sessions as the pool maximum
+    /// were dropped or the wait timeout is over. The connection goes back to the pool on drop,
+    /// after its uncommitted work is rolled back.
+    pub fn get(&self) -> Result<Connection, OracleError> {
+        let env = &self.inner.env;
+        let error = ErrorHandle::new(env)?;
+        let deadline = self.inner.wait_timeout.map(|timeout| Instant::now() + timeout);
+        let mut dropped = 0;
+        loop {
+            let handle = error.call(|error| oci_session_get(env.as_ptr(), error, &self.inner.name))?;
+            if self.needs_ping(handle) {
+                if let Err(err) = oci_ping(handle, error.as_ptr()) {
+                    let _ = oci_session_release(handle, error.as_ptr(), OCISessionReleaseMode::DropSession);
+                    dropped += 1;
+                    if dropped >= self.inner.max || deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+                        return Err(err);
+                    }
+                    continue;
+                }
+            }
+            let service = ServiceContext::from_pool(env, handle);
+            return Ok(connection::pooled(self.clone(), service, error));
+        }
+    }
+
+    /// Environment the pool was created in.
+    pub fn environment(&self) -> &Environment {
+        &self.inner.env
+    }
+
+    /// Number of sessions in use.
+    pub fn busy_count(&self) -> Result<u32, OracleError> {
+        self.count(OCIAttribute::SessionPoolBusyCount)
+    }
+
+    /// Number of open sessions, busy or idle.
+    pub fn open_count(&self) -> Result<u32, OracleError> {
+        self.count(OCIAttribute::SessionPoolOpenCount)
+    }
+
+    fn count(&self, attr_type: OCIAttribute) -> Result<u32, OracleError> {
+        let error = self.inner.error.lock().unwrap_or_else(|err| err.into_inner());
+        let (count, _) = oci_handle_attr_get::<c_uint>(self.inner.handle as *mut c_void,
+                                                       OCIHandleType::SessionPool,
+                                                       attr_type,
+                                                       error.as_ptr())?;
+        Ok(count)
+    }
+
+    /// Whether the session was idle for longer than the ping interval.
+    fn needs_ping(&self, handle: *mut OCISvcCtx) -> bool {
+        let mut released = self.inner.released.lock().unwrap_or_else(|err| err.into_inner());
+        match (released.remove(&(handle as usize)), self.inner.ping_interval) {
+            (Some(time), Some(interval)) => time.elapsed() >= interval,
+            _                            => false,
+        }
+    }
+}
+
+/// Gives the session in `service` back to `pool`, or drops it from the pool if it is `unusable`.
+pub fn release(pool: &SessionPool, service: &ServiceContext, error: &ErrorHandle, unusable: bool) {
+    let mode = if unusable { OCISessionReleaseMode::DropSession } else { OCISessionReleaseMode::Default };
+    let idle = oci_session_release(service.as_ptr(), error.as_ptr(), mode).is_ok() && !unusable;
+    // Read before locking `released`, the pool error handle has a lock of its own.
+    let open = pool.open_count();
+    let mut released = pool.inner.released.lock().unwrap_or_else(|err| err.into_inner());
+    let key = service.as_ptr() as usize;
+    if idle {
+        released.insert(key, Instant::now());
+    } else {
+        released.remove(&key);
+    }
+    // Sessions OCI closes itself, e.g. once idle for the pool timeout, are never seen here. They
+    // are the ones idle the longest, so the oldest entries are dropped while there are more than
+    // open sessions, before a new session can get the same address.
+    if let Ok(open) = open {
+        while released.len() > open as usize {
+            let oldest = released.iter().min_by_key(|&(_, &time)| time).map(|(&key, _)| key);
+            if let Some(oldest) = oldest {
+                released.remove(&oldest);
+            }
+        }
+    }
+}
+
+/// Configuration of a new [`SessionPool`](struct.SessionPool.html).
+#[derive(Clone)]
+pub struct SessionPoolBuilder {
+    username:       String,
+    password:       String,
+    connect_string: String,
+    env:            Option<Environment>,
+    min:            u32,
+    max:            u32,
+    increment:      u32,
+    get_mode:       PoolGetMode,
+    timeout:        Option<Duration>,
+    wait_timeout:   Option<Duration>,
+    ping_interval:  Option<Duration>,
+}
+
+impl SessionPoolBuilder {
+    /// Creates the pool in `env` rather than in a new environment.
+    pub fn environment(mut self, env: &Environment) -> SessionPoolBuilder {
+        self.env = Some(env.clone());
+        self
+    }
+
+    /// Number of sessions opened with the pool and kept open, 1 by default.
+    pub fn min(mut self, min: u32) -> SessionPoolBuilder {
+        self.min = min;
+        self
+    }
+
+    /// Maximum number of open sessions, 10 by default.
+    pub fn max(mut self, max: u32) -> SessionPoolBuilder {
+        self.max = max;
+        self
+    }
+
+    /// Number of sessions opened at once when more are needed, 1 by default.
+    pub fn increment(mut self, increment: u32) -> SessionPoolBuilder {
+        self.increment = increment;
+        self
+    }
+
+    /// What to do when every session is busy, `PoolGetMode::Wait` by default.
+    pub fn get_mode(mut self, get_mode: PoolGetMode) -> SessionPoolBuilder {
+        self.get_mode = get_mode;
+        self
+    }
+
+    /// Closes sessions above the minimum once they are idle for `timeout`, by default they are kept.
+    pub fn timeout(mut self, timeout: Duration) -> SessionPoolBuilder {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Fails a get in `PoolGetMode::Wait` after waiting `timeout`, by default it waits indefinitely.
+    pub fn wait_timeout(mut self, timeout: Duration) -> SessionPoolBuilder {
+        self.wait_timeout = Some(timeout);
+        self
+    }
+
+    /// Pings sessions idle for longer than `interval` before handing them out, 60 seconds by
+    /// default. `None` turns pinging off.
+    pub fn ping_interval(mut self, interval: Option<Duration>) -> SessionPoolBuilder {
+        self.ping_interval = interval;
+        self
+    }
+
+    /// Creates the pool with [`oci_session_pool_create`](fn.oci_session_pool_create.html)
+    /// and opens its minimum number of sessions.
+    pub fn build(self) -> Result<SessionPool, OracleError> {
+        if self.max == 0 || self.min > self.max {
+            return Err(OracleError::new(&format!("Invalid session pool size {}..{}", self.min, self.max),
+                                        "SessionPoolBuilder::build"));
+        }
+        let env = match self.env {
+            Some(env) => env,
+            None      => Environment::new()?,
+        };
+        let error = ErrorHandle::new(&env)?;
+        let handle = oci_handle_alloc(env.as_ptr(), OCIHandleType::SessionPool)? as *mut OCISPool;
+        let mut pool = SessionPoolHandle {
+            handle,
+            name:          Vec::new(),
+            created:       false,
+            max:           self.max,
+            wait_timeout:  self.wait_timeout,
+            ping_interval: self.ping_interval,
+            released:      Mutex::new(HashMap::new()),
+            error:         Mutex::new(error),
+            env,
+        };
+        let encoding = pool.env.encoding();
+        {
+            let error = pool.error.get_mut().unwrap_or_else(|err| err.into_inner());
+            pool.name = oci_session_pool_create(pool.env.as_ptr(),
+                                                error.as_ptr(),
+                                                handle,
+                                                &encoding.encode(&self.connect_string)?,
+                                                self.min,
+                                                self.max,
+                                                self.increment,
+                                                &encoding.encode(&self.username)?,
+                                                &encoding.encode(&self.password)?)?;
+            pool.created = true;
+            let mut get_mode = self.get_mode as c_uchar;
+            set_attr(handle, &mut get_mode, OCIAttribute::SessionPoolGetMode, error)?;
+            if let Some(timeout) = self.timeout {
+                let mut seconds = timeout.as_secs().min(c_uint::MAX as u64) as c_uint;
+                set_attr(handle, &mut seconds, OCIAttribute::SessionPoolTimeout, error)?;
+            }
+            if let Some(timeout) = self.wait_timeout {
+                let mut millis = timeout.as_millis().min(c_uint::MAX as u128) as c_uint;
+                set_attr(handle, &mut millis, OCIAttribute::SessionPoolWaitTimeout, error)?;
+            }
+        }
+        Ok(SessionPool { inner: Arc::new(pool) })
+    }
+}
+
+fn set_attr<T>(handle: *mut OCISPool,
+               value: &mut T,
+               attr_type: OCIAttribute,
+               error: &ErrorHandle) -> Result<(), OracleError> {
+    oci_attr_set(handle as *mut c_void,
+                 OCIHandleType::SessionPool,
+                 value as *mut T as *mut c_void,
+                 attr_type,
+                 error.as_ptr())
+}
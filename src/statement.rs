@@ -0,0 +1,1174 @@
+//! Prepared statements and result sets
+
+use std::cmp;
+use std::mem;
+use std::ptr;
+use std::slice;
+use std::str;
+use std::sync::Arc;
+use {c_void, c_int, c_uchar, c_schar, c_short, c_ushort, c_uint, OCIEnv, OCIStmt, OCIError, OCIBind, OCIDateTime,
+     OCIInterval, OCILobLocator, OCIHandleType, OCIAttribute, OCIDescribeAttribute, OCIDescriptorType,
+     OCIDataType, OCIBindMode, OCIExecuteMode, OCIFetchOrientation, OCIStmtPrepare2Mode, OCIStmtReleaseMode,
+     OciStatus, OracleError};
+use {oci_stmt_prepare2, oci_stmt_execute, oci_stmt_release, oci_param_get, oci_attr_get,
+     oci_handle_attr_get, oci_descriptor_free, oci_define_by_pos, oci_stmt_fetch2,
+     oci_bind_by_pos, oci_bind_by_name, oci_bind_dynamic, oci_lob_locator_assign, oci_batch_error_get,
+     oci_error_get};
+use charset::Encoding;
+use connection::{self, Connection};
+use datetime;
+use handle::{Environment, ErrorHandle, Descriptor};
+use interval;
+use lob::Lob;
+use value::{self, SqlValue, ToSql, FromSql};
+
+/// Smallest define buffer, big enough for dates converted to text.
+const MIN_BUFFER_SIZE: usize = 128;
+
+/// Size of an `OCINumber`.
+const NUMBER_SIZE: usize = 22;
+
+/// Maximum number of bytes per character in the client character set.
+const MAX_BYTES_PER_CHAR: usize = 4;
+
+/// Size of a `DATE` in the internal format.
+const DATE_SIZE: usize = 7;
+
+/// Default define buffer of `LONG` and `LONG RAW` columns.
+const LONG_SIZE: usize = 64 * 1024;
+
+/// Length preceding the data of `SQLT_LVC` and `SQLT_LVB` buffers.
+const LONG_LENGTH_SIZE: usize = 4;
+
+/// `OCI_ONE_PIECE`
+const ONE_PIECE: c_uchar = 0;
+
+/// Placeholder of a bind variable.
+#[derive(Clone, Debug, PartialEq)]
+pub enum BindIndex {
+    /// 1-based position of the placeholder in the statement.
+    Position(usize),
+
+    /// Name of the placeholder including the leading colon, e.g. `:id`.
+    Name(String),
+}
+
+impl From<usize> for BindIndex {
+    fn from(position: usize) -> BindIndex {
+        BindIndex::Position(position)
+    }
+}
+
+impl<'a> From<&'a str> for BindIndex {
+    fn from(name: &'a str) -> BindIndex {
+        BindIndex::Name(name.to_string())
+    }
+}
+
+/// Type of a prepared statement, read from `OCI_ATTR_STMT_TYPE`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StatementType {
+    /// `OCI_STMT_SELECT`, including queries starting with `WITH`.
+    Select,
+
+    /// `OCI_STMT_UPDATE`
+    Update,
+
+    /// `OCI_STMT_DELETE`
+    Delete,
+
+    /// `OCI_STMT_INSERT`
+    Insert,
+
+    /// `OCI_STMT_MERGE`
+    Merge,
+
+    /// `OCI_STMT_CREATE`
+    Create,
+
+    /// `OCI_STMT_DROP`
+    Drop,
+
+    /// `OCI_STMT_ALTER`
+    Alter,
+
+    /// `OCI_STMT_BEGIN`, a PL/SQL block.
+    Begin,
+
+    /// `OCI_STMT_DECLARE`, a PL/SQL block.
+    Declare,
+
+    /// `OCI_STMT_CALL`
+    Call,
+
+    /// Any other statement, e.g. `TRUNCATE` or `GRANT`, with the code OCI returned.
+    Other(u16),
+}
+
+impl StatementType {
+    fn from_code(code: c_ushort) -> StatementType {
+        match code {
+            1  => StatementType::Select,
+            2  => StatementType::Update,
+            3  => StatementType::Delete,
+            4  => StatementType::Insert,
+            5  => StatementType::Create,
+            6  => StatementType::Drop,
+            7  => StatementType::Alter,
+            8  => StatementType::Begin,
+            9  => StatementType::Declare,
+            10 => StatementType::Call,
+            16 => StatementType::Merge,
+            _  => StatementType::Other(code),
+        }
+    }
+
+    /// Whether the statement returns rows.
+    pub fn is_query(&self) -> bool {
+        *self == StatementType::Select
+    }
+
+    /// Whether the statement is an `INSERT`, `UPDATE`, `DELETE` or `MERGE`.
+    pub fn is_dml(&self) -> bool {
+        matches!(*self, StatementType::Insert | StatementType::Update | StatementType::Delete | StatementType::Merge)
+    }
+
+    /// Whether the statement is a `CREATE`, `DROP` or `ALTER`.
+    pub fn is_ddl(&self) -> bool {
+        matches!(*self, StatementType::Create | StatementType::Drop | StatementType::Alter)
+    }
+
+    /// Whether the statement is a PL/SQL block or a `CALL`.
+    pub fn is_plsql(&self) -> bool {
+        matches!(*self, StatementType::Begin | StatementType::Declare | StatementType::Call)
+    }
+}
+
+/// Metadata of a column of a query, read from its parameter descriptor.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ColumnInfo {
+    name:           String,
+    sql_type:       u16,
+    data_size:      u16,
+    precision:      i16,
+    scale:          i8,
+    nullable:       bool,
+    char_semantics: bool,
+    char_length:    u16,
+    type_name:      Option<String>,
+    schema_name:    Option<String>,
+    collation_id:   Option<u32>,
+}
+
+impl ColumnInfo {
+    /// Reads the attributes of the column described by `param`, names are converted from the
+    /// client character set with `encoding`.
+    fn describe(param: *mut c_void, error: *mut OCIError, encoding: Encoding) -> Result<ColumnInfo, OracleError> {
+        let (sql_type, _) = oci_attr_get::<c_ushort>(param, error, OCIDescribeAttribute::DataType)?;
+        let (data_size, _) = oci_attr_get::<c_ushort>(param, error, OCIDescribeAttribute::DataSize)?;
+        // Precision and scale are signed in the implicit describe of a select-list.
+        let (precision, _) = oci_attr_get::<c_short>(param, error, OCIDescribeAttribute::Precision)?;
+        let (scale, _) = oci_attr_get::<c_schar>(param, error, OCIDescribeAttribute::Scale)?;
+        let (nullable, _) = oci_attr_get::<c_uchar>(param, error, OCIDescribeAttribute::IsNull)?;
+        let (char_used, _) = oci_attr_get::<c_uchar>(param, error, OCIDescribeAttribute::CharUsed)?;
+        let (char_length, _) = oci_attr_get::<c_ushort>(param, error, OCIDescribeAttribute::CharLength)?;
+        let type_name = text_attr(param, error, OCIDescribeAttribute::TypeName, encoding)?;
+        let schema_name = text_attr(param, error, OCIDescribeAttribute::SchemaName, encoding)?;
+        Ok(ColumnInfo {
+            name:           text_attr(param, error, OCIDescribeAttribute::Name, encoding)?,
+            sql_type,
+            data_size,
+            precision,
+            scale,
+            nullable:       nullable != 0,
+            char_semantics: char_used != 0,
+            char_length,
+            type_name:      Some(type_name).filter(|name| !name.is_empty()),
+            schema_name:    Some(schema_name).filter(|name| !name.is_empty()),
+            // Unknown to clients before 12.2.
+            collation_id:   oci_attr_get::<c_uint>(param, error, OCIDescribeAttribute::CollationId)
+                                .ok().map(|(id, _)| id),
+        })
+    }
+
+    /// Name of the column, or the expression of the select-list.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// SQL type code of the column, e.g. `2` for `NUMBER` or `1` for `VARCHAR2`.
+    pub fn sql_type(&self) -> u16 {
+        self.sql_type
+    }
+
+    /// Maximum size of the data in bytes.
+    pub fn data_size(&self) -> u16 {
+        self.data_size
+    }
+
+    /// Precision of a number, 0 if not given.
+    pub fn precision(&self) -> i16 {
+        self.precision
+    }
+
+    /// Scale of a number, -127 for a `NUMBER` declared without precision and scale.
+    pub fn scale(&self) -> i8 {
+        self.scale
+    }
+
+    /// Whether the column may be `NULL`.
+    pub fn is_nullable(&self) -> bool {
+        self.nullable
+    }
+
+    /// Whether the length of the column is in characters rather than bytes.
+    pub fn char_semantics(&self) -> bool {
+        self.char_semantics
+    }
+
+    /// Length of a character column in characters.
+    pub fn char_length(&self) -> u16 {
+        self.char_length
+    }
+
+    /// Name of the type of an object or `REF` column.
+    pub fn type_name(&self) -> Option<&str> {
+        self.type_name.as_deref()
+    }
+
+    /// Schema of the type of an object or `REF` column.
+    pub fn schema_name(&self) -> Option<&str> {
+        self.schema_name.as_deref()
+    }
+
+    /// Collation id of a character column, `None` if the client does not report collations.
+    pub fn collation_id(&self) -> Option<u32> {
+        self.collation_id
+    }
+}
+
+/// Value bound to a placeholder, kept alive until the statement is dropped or rebound.
+struct Bind {
+    index:     BindIndex,
+    value:     SqlValue,
+    indicator: c_short,
+    // Descriptor the value is converted to for types OCI only accepts as descriptors.
+    #[allow(dead_code)]
+    descriptor: Option<Descriptor>,
+    // Descriptor or LOB locator bound by reference, null for values bound by their bytes.
+    pointer:   *mut c_void,
+}
+
+/// Placeholder of a `RETURNING INTO` clause, filled by OCI through `returning_in` and `returning_out`.
+struct Returning {
+    index:      BindIndex,
+    data_type:  OCIDataType,
+    size:       usize,
+    error:      *mut OCIError,
+    // Indicator of the NULL passed in for the placeholder.
+    null:       c_short,
+    // Values returned by every iteration of the last execution.
+    iterations: Vec<Vec<Slot>>,
+}
+
+/// Buffer of a value returned into a `RETURNING INTO` placeholder.
+struct Slot {
+    buffer:      Vec<u8>,
+    length:      c_uint,
+    indicator:   c_short,
+    return_code: c_ushort,
+}
+
+impl Slot {
+    fn new(size: usize) -> Slot {
+        Slot { buffer: vec![0; size], length: 0, indicator: 0, return_code: 0 }
+    }
+
+    fn value(&self, stmt: &Statement, data_type: OCIDataType) -> Result<SqlValue, OracleError> {
+        if self.indicator == -1 {
+            return Ok(SqlValue::null(data_type));
+        }
+        check_fetched(self.indicator, self.return_code, "Slot::value")?;
+        buffer_value(stmt, data_type, &self.buffer[..self.length as usize])
+    }
+}
+
+/// Column of values bound as an array by [`Statement::execute_many`](struct.Statement.html#method.execute_many).
+struct ArrayBind {
+    position:    usize,
+    data:        Vec<u8>,
+    indicators:  Vec<c_short>,
+    lengths:     Vec<c_ushort>,
+    pointers:    Vec<*mut c_void>,
+    // Descriptors and LOB locators the pointers refer to.
+    #[allow(dead_code)]
+    descriptors: Vec<Descriptor>,
+    #[allow(dead_code)]
+    values:      Vec<SqlValue>,
+}
+
+/// Error of a single row of [`Statement::execute_many`](struct.Statement.html#method.execute_many).
+#[derive(Debug)]
+pub struct BatchError {
+    row:   usize,
+    error: OracleError,
+}
+
+impl BatchError {
+    /// Zero-based index of the failed row.
+    pub fn row(&self) -> usize {
+        self.row
+    }
+
+    /// Error the row failed with.
+    pub fn error(&self) -> &OracleError {
+        &self.error
+    }
+}
+
+/// Statement prepared with [`oci_stmt_prepare2`](fn.oci_stmt_prepare2.html).
+///
+/// The statement is released with [`oci_stmt_release`](fn.oci_stmt_release.html) on drop.
+pub struct Statement<'conn> {
+    conn:    &'conn Connection,
+    handle:  *mut OCIStmt,
+    key:     String,
+    release: OCIStmtReleaseMode,
+    // Boxed so that the buffers do not move when more values are bound.
+    #[allow(clippy::vec_box)]
+    binds:  Vec<Box<Bind>>,
+    arrays: Vec<ArrayBind>,
+    // Boxed so that the contexts of the callbacks do not move.
+    #[allow(clippy::vec_box)]
+    returning: Vec<Box<Returning>>,
+    long_size: usize,
+}
+
+impl<'conn> Statement<'conn> {
+    /// Prepares `sql` on `conn`.
+    pub fn new(conn: &'conn Connection, sql: &str) -> Result<Statement<'conn>, OracleError> {
+        prepare(conn, sql, "", OCIStmtPrepare2Mode::Default)
+    }
+
+    /// Raw `OCIStmt` pointer.
+    pub fn as_ptr(&self) -> *mut OCIStmt {
+        self.handle
+    }
+
+    /// Binds `value` to the placeholder at `index`, a 1-based position or a name like `":id"`.
+    ///
+    /// The value is copied, so it may be dropped before the statement is executed.
+    /// `NULL` is bound with `None`. Binding the same placeholder again replaces the value.
+    pub fn bind<I, T>(&mut self, index: I, value: &T) -> Result<(), OracleError>
+        where I: Into<BindIndex>, T: ToSql + ?Sized {
+        let value = self.encode(value.to_sql()?)?;
+        let descriptor = match descriptor_type(value.data_type()) {
+            Some(_) if value::locator(&value).is_some() => None,
+            Some(dtype) => {
+                let descriptor = Descriptor::new(self.conn.environment(), dtype)?;
+                if !value.is_null() {
+                    write_descriptor(self, &descriptor, &value)?;
+                }
+                Some(descriptor)
+            },
+            None => None,
+        };
+        let pointer = match (value::locator(&value), descriptor.as_ref()) {
+            (Some(locator), _)       => locator.as_ptr(),
+            (None, Some(descriptor)) => descriptor.as_ptr(),
+            (None, None)             => ptr::null_mut(),
+        };
+        let mut bind = Box::new(Bind {
+            index:     index.into(),
+            indicator: if value.is_null() { -1 } else { 0 },
+            value,
+            descriptor,
+            pointer,
+        });
+        let (data, size) = if bind.pointer.is_null() {
+            (bind.value.as_bytes().as_ptr() as *mut c_void, bind.value.as_bytes().len())
+        } else {
+            (&mut bind.pointer as *mut *mut c_void as *mut c_void, mem::size_of::<*mut c_void>())
+        };
+        let data_type = bind.value.data_type();
+        match bind.index {
+            BindIndex::Position(position) =>
+                oci_bind_by_pos(self.handle, self.error(), position, data, size, data_type,
+                                &mut bind.indicator, ptr::null_mut(), OCIBindMode::Default)?,
+            BindIndex::Name(ref name) =>
+                oci_bind_by_name(self.handle, self.error(), name, data, size, data_type,
+                                 &mut bind.indicator, ptr::null_mut(), OCIBindMode::Default)?,
+        };
+        self.arrays.retain(|array| BindIndex::Position(array.position) != bind.index);
+        self.returning.retain(|returning| returning.index != bind.index);
+        match self.binds.iter().position(|old| old.index == bind.index) {
+            Some(old) => self.binds[old] = bind,
+            None      => self.binds.push(bind),
+        }
+        Ok(())
+    }
+
+    /// Binds the placeholder at `index` of a `RETURNING ... INTO` clause, which receives values of
+    /// `data_type` of up to `size` bytes, e.g. `OCIDataType::NumericWithLength` and 22 for a `NUMBER`.
+    ///
+    /// The values are provided by OCI at execution (`OCI_DATA_AT_EXEC` with `OCIBindDynamic()`)
+    /// and read afterwards with [`returned`](#method.returned). Types bound as descriptors,
+    /// e.g. timestamps and LOBs, are not supported.
+    pub fn bind_returning<I>(&mut self, index: I, data_type: OCIDataType, size: usize) -> Result<(), OracleError>
+        where I: Into<BindIndex> {
+        if descriptor_type(data_type).is_some() || size == 0 || size > c_int::MAX as usize {
+            return Err(OracleError::new(&format!("{:?} of {} bytes cannot be returned into a placeholder",
+                                                 data_type, size),
+                                        "Statement::bind_returning"));
+        }
+        let mut returning = Box::new(Returning {
+            index:      index.into(),
+            data_type,
+            size,
+            error:      self.error(),
+            null:       -1,
+            iterations: Vec::new(),
+        });
+        let context = &mut *returning as *mut Returning as *mut c_void;
+        let handle = match returning.index {
+            BindIndex::Position(position) =>
+                oci_bind_by_pos(self.handle, self.error(), position, ptr::null_mut(), size, data_type,
+                                ptr::null_mut(), ptr::null_mut(), OCIBindMode::DataAtExec)?,
+            BindIndex::Name(ref name) =>
+                oci_bind_by_name(self.handle, self.error(), name, ptr::null_mut(), size, data_type,
+                                 ptr::null_mut(), ptr::null_mut(), OCIBindMode::DataAtExec)?,
+        };
+        oci_bind_dynamic(handle, self.error(), context, returning_in, context, returning_out)?;
+        self.binds.retain(|bind| bind.index != returning.index);
+        self.arrays.retain(|array| BindIndex::Position(array.position) != returning.index);
+        match self.returning.iter().position(|old| old.index == returning.index) {
+            Some(old) => self.returning[old] = returning,
+            None      => self.returning.push(returning),
+        }
+        Ok(())
+    }
+
+    /// Values returned into the placeholder at `index` by the last execution, with a list for
+    /// every iteration holding a value for every row it affected.
+    ///
+    /// [`execute`](#method.execute) runs one iteration, [`execute_many`](#method.execute_many)
+    /// one for every row.
+    pub fn returned<I: Into<BindIndex>>(&self, index: I) -> Result<Vec<Vec<SqlValue>>, OracleError> {
+        let index = index.into();
+        let returning = match self.returning.iter().find(|returning| returning.index == index) {
+            Some(returning) => returning,
+            None => return Err(OracleError::new(&format!("{:?} is not bound with bind_returning", index),
+                                                "Statement::returned")),
+        };
+        returning.iterations.iter()
+            .map(|rows| rows.iter().map(|slot| slot.value(self, returning.data_type)).collect())
+            .collect()
+    }
+
+    /// Number of rows inserted, updated, deleted or merged by the last execution, or fetched so
+    /// far for a query.
+    pub fn rows_affected(&self) -> Result<u64, OracleError> {
+        match oci_handle_attr_get::<u64>(self.handle as *mut c_void,
+                                         OCIHandleType::Statement,
+                                         OCIAttribute::Ub8RowCount,
+                                         self.error()) {
+            Ok((count, _)) => Ok(count),
+            // OCI_ATTR_UB8_ROW_COUNT is unknown to clients before 12.1.
+            Err(_) => oci_handle_attr_get::<c_uint>(self.handle as *mut c_void,
+                                                    OCIHandleType::Statement,
+                                                    OCIAttribute::RowCount,
+                                                    self.error()).map(|(count, _)| count as u64),
+        }
+    }
+
+    /// Type of the statement, e.g. `StatementType::Select`.
+    pub fn statement_type(&self) -> Result<StatementType, OracleError> {
+        let (code, _) = oci_handle_attr_get::<c_ushort>(self.handle as *mut c_void,
+                                                        OCIHandleType::Statement,
+                                                        OCIAttribute::StmtType,
+                                                        self.error())?;
+        Ok(StatementType::from_code(code))
+    }
+
+    /// Executes the statement once, a query is executed without fetching its rows.
+    ///
+    /// Returns the number of rows inserted, updated, deleted or merged, 0 for other statements.
+    /// The transaction is committed on success if autocommit is on for the connection.
+    pub fn execute(&mut self) -> Result<u64, OracleError> {
+        // Queries are executed with no iterations, anything else with one.
+        let iters = if self.statement_type()?.is_query() { 0 } else { 1 };
+        self.clear_returned();
+        let service = self.conn.service_context().as_ptr();
+        self.conn.error_handle().call(|error| oci_stmt_execute(service, self.handle, error, iters, self.mode()))?;
+        self.rows_affected()
+    }
+
+    /// Executes a DML statement once for every row of `rows` in a single round trip.
+    ///
+    /// A row holds the values of the positional placeholders, which are bound column by column
+    /// as arrays and replace values bound before. Rows that fail do not stop the others
+    /// (`OCI_BATCH_ERRORS`), their errors are returned instead. The transaction is committed
+    /// afterwards if autocommit is on for the connection.
+    pub fn execute_many<'v, R>(&mut self, rows: &[R]) -> Result<Vec<BatchError>, OracleError>
+        where R: AsRef<[&'v dyn ToSql]> {
+        let width = match rows.first() {
+            Some(row) => row.as_ref().len(),
+            None      => return Ok(Vec::new()),
+        };
+        if rows.iter().any(|row| row.as_ref().len() != width) {
+            return Err(OracleError::new("Rows have different numbers of values", "Statement::execute_many"));
+        }
+        let mut arrays = Vec::with_capacity(width);
+        for column in 0..width {
+            let values = rows.iter()
+                .map(|row| row.as_ref()[column].to_sql().and_then(|value| self.encode(value)))
+                .collect::<Result<Vec<_>, _>>()?;
+            arrays.push(self.bind_array(column + 1, values)?);
+        }
+        self.binds.retain(|bind| match bind.index {
+            BindIndex::Position(position) => position > width,
+            BindIndex::Name(_)            => true,
+        });
+        self.arrays.retain(|array| array.position > width);
+        self.arrays.extend(arrays);
+        self.returning.retain(|returning| match returning.index {
+            BindIndex::Position(position) => position > width,
+            BindIndex::Name(_)            => true,
+        });
+        self.clear_returned();
+        let service = self.conn.service_context().as_ptr();
+        match self.conn.error_handle().call(|error| oci_stmt_execute(service, self.handle, error, rows.len(),
+                                                                     OCIExecuteMode::BatchErrors)) {
+            Ok(()) => {},
+            // ORA-24381: error(s) in array DML
+            Err(ref err) if err.code() == 24381 => {},
+            Err(err) => return Err(err),
+        }
+        let errors = self.batch_errors()?;
+        if self.conn.autocommit() {
+            self.conn.commit()?;
+        }
+        Ok(errors)
+    }
+
+    /// Executes the query and returns an iterator over its rows.
+    ///
+    /// Fails without executing if the statement is not a query.
+    pub fn query(&mut self) -> Result<Rows<'_>, OracleError> {
+        let stmt_type = self.statement_type()?;
+        if !stmt_type.is_query() {
+            return Err(OracleError::new(&format!("{:?} statement is not a query", stmt_type), "Statement::query"));
+        }
+        let service = self.conn.service_context().as_ptr();
+        self.conn.error_handle().call(|error| oci_stmt_execute(service, self.handle, error, 0, self.mode()))?;
+        let info = self.columns()?;
+        let columns = self.define(&info)?;
+        Ok(Rows { stmt: self, columns, info, done: false })
+    }
+
+    /// Maximum size in bytes of the `LONG` and `LONG RAW` values fetched by the next
+    /// [`query`](#method.query), 64 KiB by default. Longer values fail with `ORA-01406`.
+    ///
+    /// Sizes that do not fit the define buffer of OCI, up to 2 GiB, are rejected.
+    pub fn set_long_size(&mut self, size: usize) -> Result<(), OracleError> {
+        if size > c_int::MAX as usize - LONG_LENGTH_SIZE {
+            return Err(OracleError::new(&format!("LONG size of {} bytes is too large", size),
+                                        "Statement::set_long_size"));
+        }
+        self.long_size = size;
+        Ok(())
+    }
+
+    /// Metadata of every column of the select-list, once the query is executed or described
+    /// with `OCIExecuteMode::DescribeOnly`.
+    pub fn columns(&self) -> Result<Vec<ColumnInfo>, OracleError> {
+        let (count, _) = oci_handle_attr_get::<c_uint>(self.handle as *mut c_void,
+                                                       OCIHandleType::Statement,
+                                                       OCIAttribute::ParamCount,
+                                                       self.error())?;
+        let encoding = self.conn.environment().encoding();
+        (1..=count as usize).map(|position| {
+            let param = oci_param_get(self.handle, self.error(), position)?;
+            let info = ColumnInfo::describe(param, self.error(), encoding);
+            let _ = oci_descriptor_free(param, OCIDescriptorType::Parameter);
+            info
+        }).collect()
+    }
+
+    /// Binds `values` as an array at `position`.
+    fn bind_array(&self, position: usize, values: Vec<SqlValue>) -> Result<ArrayBind, OracleError> {
+        let data_type = values.iter()
+            .find(|value| !value.is_null())
+            .map_or(OCIDataType::Char, |value| value.data_type());
+        if values.iter().any(|value| !value.is_null() && value.data_type() != data_type) {
+            return Err(OracleError::new(&format!("Values at position {} have different data types", position),
+                                        "Statement::execute_many"));
+        }
+        let mut array = ArrayBind {
+            position,
+            data:        Vec::new(),
+            indicators:  values.iter().map(|value| if value.is_null() { -1 } else { 0 }).collect(),
+            lengths:     Vec::new(),
+            pointers:    Vec::new(),
+            descriptors: Vec::new(),
+            values:      Vec::new(),
+        };
+        let (data, size, lengths) = match descriptor_type(data_type) {
+            Some(dtype) => {
+                for value in &values {
+                    match value::locator(value) {
+                        Some(locator) => array.pointers.push(locator.as_ptr()),
+                        None => {
+                            let descriptor = Descriptor::new(self.conn.environment(), dtype)?;
+                            if !value.is_null() {
+                                write_descriptor(self, &descriptor, value)?;
+                            }
+                            array.pointers.push(descriptor.as_ptr());
+                            array.descriptors.push(descriptor);
+                        },
+                    }
+                }
+                (array.pointers.as_mut_ptr() as *mut c_void, mem::size_of::<*mut c_void>(), ptr::null_mut())
+            },
+            None => {
+                let elements = values.iter().map(widen).collect::<Result<Vec<_>, _>>()?;
+                let size = cmp::max(elements.iter().map(Vec::len).max().unwrap_or(0), 1);
+                if size > c_ushort::MAX as usize {
+                    return Err(OracleError::new(&format!("Values at position {} are too long for an array bind",
+                                                         position),
+                                                "Statement::execute_many"));
+                }
+                array.data.reserve(size * elements.len());
+                for element in &elements {
+                    array.data.extend_from_slice(element);
+                    array.data.resize(array.data.len() + size - element.len(), 0);
+                    array.lengths.push(element.len() as c_ushort);
+                }
+                (array.data.as_mut_ptr() as *mut c_void, size, array.lengths.as_mut_ptr())
+            },
+        };
+        oci_bind_by_pos(self.handle, self.error(), position, data, size, data_type,
+                        array.indicators.as_mut_ptr(), lengths, OCIBindMode::Default)?;
+        array.values = values;
+        Ok(array)
+    }
+
+    /// Reads the row errors of the last array DML.
+    fn batch_errors(&self) -> Result<Vec<BatchError>, OracleError> {
+        let (count, _) = oci_handle_attr_get::<c_uint>(self.handle as *mut c_void,
+                                                       OCIHandleType::Statement,
+                                                       OCIAttribute::NumDmlErrors,
+                                                       self.error())?;
+        if count == 0 {
+            return Ok(Vec::new());
+        }
+        let row_error = ErrorHandle::new(self.conn.environment())?;
+        let mut errors = Vec::with_capacity(count as usize);
+        for index in 0..count as usize {
+            let handle = oci_batch_error_get(self.error(), row_error.as_ptr(), index)?;
+            let (row, _) = oci_handle_attr_get::<c_uint>(handle as *mut c_void,
+                                                         OCIHandleType::Error,
+                                                         OCIAttribute::DmlRowOffset,
+                                                         self.error())?;
+            let error = oci_error_get(handle, "Statement::execute_many");
+            errors.push(BatchError { row: row as usize, error });
+        }
+        Ok(errors)
+    }
+
+    /// `value` with text converted from UTF-8 into the client character set.
+    fn encode(&self, value: SqlValue) -> Result<SqlValue, OracleError> {
+        let encoding = self.conn.environment().encoding();
+        if value.data_type() != OCIDataType::Char || value.is_null() || encoding == Encoding::Utf8 {
+            return Ok(value);
+        }
+        let text = str::from_utf8(value.as_bytes())
+            .map_err(|_| OracleError::new("Text is not valid UTF-8", "Statement::bind"))?;
+        Ok(SqlValue::new(OCIDataType::Char, encoding.encode(text)?))
+    }
+
+    fn clear_returned(&mut self) {
+        for returning in &mut self.returning {
+            returning.iterations.clear();
+        }
+    }
+
+    fn mode(&self) -> OCIExecuteMode {
+        if self.conn.autocommit() { OCIExecuteMode::CommitOnSuccess } else { OCIExecuteMode::Default }
+    }
+
+    fn error(&self) -> *mut OCIError {
+        self.conn.error_handle().as_ptr()
+    }
+
+    fn env(&self) -> *mut OCIEnv {
+        self.conn.environment().as_ptr()
+    }
+
+    /// Allocates and defines an output buffer for every column of the select-list described by `info`.
+    fn define(&self, info: &[ColumnInfo]) -> Result<Vec<Column>, OracleError> {
+        let mut columns = info.iter()
+            .map(|info| Column::new(self.conn.environment(), info.sql_type, info.data_size as usize, self.long_size))
+            .collect::<Result<Vec<_>, _>>()?;
+        // Buffers must not move after being defined, so they are bound only once all are allocated.
+        for (index, column) in columns.iter_mut().enumerate() {
+            let (value, size) = match column.descriptor {
+                Some(ref mut descriptor) =>
+                    (descriptor.ptr_mut() as *mut c_void, mem::size_of::<*mut c_void>()),
+                None => (column.buffer.as_mut_ptr() as *mut c_void, column.buffer.len()),
+            };
+            oci_define_by_pos(self.handle,
+                              self.error(),
+                              index + 1,
+                              value,
+                              size,
+                              column.define_type,
+                              &mut column.indicator,
+                              &mut column.length,
+                              &mut column.return_code)?;
+        }
+        Ok(columns)
+    }
+}
+
+impl<'conn> Drop for Statement<'conn> {
+    fn drop(&mut self) {
+        let _ = oci_stmt_release(self.handle, self.error(), &self.key, self.release);
+        if self.release == OCIStmtReleaseMode::Default {
+            // The cached statement still points to the bind buffers.
+            let buffers = Buffers {
+                binds:     mem::take(&mut self.binds),
+                arrays:    mem::take(&mut self.arrays),
+                returning: mem::take(&mut self.returning),
+            };
+            connection::retained_buffers(self.conn).borrow_mut().insert(self.key.clone(), buffers);
+        }
+    }
+}
+
+/// Bind buffers of a statement put back into the statement cache, kept until the statement is
+/// taken from the cache again, as OCI keeps its binds.
+#[derive(Default)]
+pub struct Buffers {
+    #[allow(clippy::vec_box)]
+    binds:     Vec<Box<Bind>>,
+    arrays:    Vec<ArrayBind>,
+    #[allow(clippy::vec_box)]
+    returning: Vec<Box<Returning>>,
+}
+
+// The pointers in the buffers refer to the buffers themselves and to handles of the connection.
+unsafe impl Send for Buffers {}
+
+/// Prepares `sql` on `conn`, or takes the statement cached under `key` if it is not empty.
+///
+/// Only statements with a key are put back into the cache when dropped.
+pub fn prepare<'conn>(conn: &'conn Connection,
+                      sql: &str,
+                      key: &str,
+                      mode: OCIStmtPrepare2Mode) -> Result<Statement<'conn>, OracleError> {
+    let key = key.to_string();
+    let handle = oci_stmt_prepare2(conn.service_context().as_ptr(),
+                                   conn.error_handle().as_ptr(),
+                                   &conn.environment().encoding().encode(sql)?,
+                                   &key,
+                                   mode)?;
+    let (release, buffers) = if key.is_empty() {
+        (OCIStmtReleaseMode::CacheDelete, None)
+    } else {
+        (OCIStmtReleaseMode::Default, connection::retained_buffers(conn).borrow_mut().remove(&key))
+    };
+    let buffers = buffers.unwrap_or_default();
+    Ok(Statement {
+        conn,
+        handle,
+        key,
+        release,
+        binds:     buffers.binds,
+        arrays:    buffers.arrays,
+        returning: buffers.returning,
+        long_size: LONG_SIZE,
+    })
+}
+
+/// In-bind callback of a `RETURNING INTO` placeholder, which passes `NULL` in.
+extern "C" fn returning_in(ictxp: *mut c_void,
+                           _bindp: *mut OCIBind,
+                           _iter: c_uint,
+                           _index: c_uint,
+                           bufpp: *mut *mut c_void,
+                           alenp: *mut c_uint,
+                           piecep: *mut c_uchar,
+                           indpp: *mut *mut c_void) -> c_int {
+    // `ictxp` is the boxed `Returning` kept by the statement.
+    let returning = unsafe { &mut *(ictxp as *mut Returning) };
+    unsafe {
+        *bufpp = ptr::null_mut();
+        *alenp = 0;
+        *piecep = ONE_PIECE;
+        *indpp = &mut returning.null as *mut c_short as *mut c_void;
+    }
+    OciStatus::Continue.code()
+}
+
+/// Out-bind callback of a `RETURNING INTO` placeholder, which gives OCI the buffer of the value
+/// returned for row `index` by iteration `iter`.
+#[allow(clippy::too_many_arguments)]
+extern "C" fn returning_out(octxp: *mut c_void,
+                            bindp: *mut OCIBind,
+                            iter: c_uint,
+                            index: c_uint,
+                            bufpp: *mut *mut c_void,
+                            alenpp: *mut *mut c_uint,
+                            piecep: *mut c_uchar,
+                            indpp: *mut *mut c_void,
+                            rcodepp: *mut *mut c_ushort) -> c_int {
+    // `octxp` is the boxed `Returning` kept by the statement.
+    let returning = unsafe { &mut *(octxp as *mut Returning) };
+    let iter = iter as usize;
+    if index == 0 {
+        // The number of rows an iteration returns is only known when the first one is.
+        let rows = oci_handle_attr_get::<c_uint>(bindp as *mut c_void,
+                                                 OCIHandleType::Bind,
+                                                 OCIAttribute::RowsReturned,
+                                                 returning.error).map_or(0, |(rows, _)| rows);
+        if returning.iterations.len() <= iter {
+            returning.iterations.resize_with(iter + 1, Vec::new);
+        }
+        returning.iterations[iter] = (0..rows).map(|_| Slot::new(returning.size)).collect();
+    }
+    let slot = returning.iterations.get_mut(iter).and_then(|rows| rows.get_mut(index as usize));
+    unsafe {
+        *piecep = ONE_PIECE;
+        match slot {
+            Some(slot) => {
+                slot.length = slot.buffer.len() as c_uint;
+                *bufpp = slot.buffer.as_mut_ptr() as *mut c_void;
+                *alenpp = &mut slot.length;
+                *indpp = &mut slot.indicator as *mut c_short as *mut c_void;
+                *rcodepp = &mut slot.return_code;
+            },
+            // The iteration returned no row.
+            None => {
+                *bufpp = ptr::null_mut();
+                *alenpp = ptr::null_mut();
+                *indpp = ptr::null_mut();
+                *rcodepp = ptr::null_mut();
+            },
+        }
+    }
+    OciStatus::Continue.code()
+}
+
+/// Makes `stmt` be removed from the statement cache rather than put back when dropped.
+pub fn delete_from_cache(stmt: &mut Statement) {
+    stmt.release = OCIStmtReleaseMode::CacheDelete;
+}
+
+/// Reads the text attribute `attr_type` of the parameter `param`, empty if it is not set.
+fn text_attr(param: *mut c_void,
+             error: *mut OCIError,
+             attr_type: OCIDescribeAttribute,
+             encoding: Encoding) -> Result<String, OracleError> {
+    let (text, len) = oci_attr_get::<*mut c_uchar>(param, error, attr_type)?;
+    if text.is_null() || len <= 0 {
+        return Ok(String::new());
+    }
+    // The text points into the descriptor, which is still allocated.
+    encoding.decode(unsafe { slice::from_raw_parts(text, len as usize) })
+}
+
+/// Array element of `value`, integers and floats are widened to 8 bytes so that a column
+/// can mix them.
+fn widen(value: &SqlValue) -> Result<Vec<u8>, OracleError> {
+    if value.is_null() {
+        return Ok(Vec::new());
+    }
+    match value.data_type() {
+        OCIDataType::Int   => Ok(i64::from_sql(value)?.to_ne_bytes().to_vec()),
+        OCIDataType::Uint  => Ok(u64::from_sql(value)?.to_ne_bytes().to_vec()),
+        OCIDataType::Float => Ok(f64::from_sql(value)?.to_ne_bytes().to_vec()),
+        _                  => Ok(value.as_bytes().to_vec()),
+    }
+}
+
+/// Descriptor type values of `data_type` are exchanged in, if any.
+fn descriptor_type(data_type: OCIDataType) -> Option<OCIDescriptorType> {
+    match data_type {
+        OCIDataType::Timestamp            => Some(OCIDescriptorType::Timestamp),
+        OCIDataType::TimestampWithTz      => Some(OCIDescriptorType::TimestampWithTz),
+        OCIDataType::TimestampWithLocalTz => Some(OCIDescriptorType::TimestampWithLocalTz),
+        OCIDataType::IntervalYearToMonth  => Some(OCIDescriptorType::IntervalYearToMonth),
+        OCIDataType::IntervalDayToSecond  => Some(OCIDescriptorType::IntervalDayToSecond),
+        OCIDataType::Blob | OCIDataType::Clob => Some(OCIDescriptorType::Lob),
+        _                                 => None,
+    }
+}
+
+/// Reads the value of `data_type` from a fetched descriptor.
+fn read_descriptor(stmt: &Statement,
+                   descriptor: &Descriptor,
+                   data_type: OCIDataType) -> Result<SqlValue, OracleError> {
+    match data_type {
+        // The define locator is reused by the next fetch, so the value gets a copy.
+        OCIDataType::Blob | OCIDataType::Clob => {
+            let mut locator = Descriptor::new(stmt.conn.environment(), OCIDescriptorType::Lob)?;
+            oci_lob_locator_assign(stmt.conn.service_context().as_ptr(), stmt.error(),
+                                   descriptor.as_ptr() as *mut OCILobLocator,
+                                   locator.ptr_mut() as *mut *mut OCILobLocator)?;
+            Ok(value::from_locator(data_type, Arc::new(locator)))
+        },
+        OCIDataType::IntervalYearToMonth | OCIDataType::IntervalDayToSecond =>
+            interval::read_interval(stmt.env(), stmt.error(), descriptor.as_ptr() as *mut OCIInterval,
+                                    data_type),
+        _ => datetime::read_timestamp(stmt.env(), stmt.error(), descriptor.as_ptr() as *mut OCIDateTime,
+                                      data_type),
+    }
+}
+
+/// Fills a descriptor allocated with the type from [`descriptor_type`] with `value`.
+fn write_descriptor(stmt: &Statement, descriptor: &Descriptor, value: &SqlValue) -> Result<(), OracleError> {
+    match value.data_type() {
+        OCIDataType::Blob | OCIDataType::Clob =>
+            Err(OracleError::new("LOB values are bound from a Lob", "Statement::bind")),
+        OCIDataType::IntervalYearToMonth | OCIDataType::IntervalDayToSecond =>
+            interval::construct_interval(stmt.env(), stmt.error(), descriptor.as_ptr() as *mut OCIInterval,
+                                         value),
+        _ => datetime::construct_timestamp(stmt.env(), stmt.error(),
+                                           descriptor.as_ptr() as *mut OCIDateTime, value),
+    }
+}
+
+/// Define buffer of a single column.
+struct Column {
+    data_type:   OCIDataType,
+    // Differs from `data_type` for `LONG` and `LONG RAW`, whose buffers start with their length.
+    define_type: OCIDataType,
+    buffer:      Vec<u8>,
+    descriptor:  Option<Descriptor>,
+    indicator:   c_short,
+    length:      c_ushort,
+    return_code: c_ushort,
+}
+
+impl Column {
+    fn new(env: &Environment, sql_type: c_ushort, size: usize, long_size: usize) -> Result<Column, OracleError> {
+        let long_size = long_size + LONG_LENGTH_SIZE;
+        let (data_type, size) = match sql_type {
+            // RAW
+            23        => (OCIDataType::Binary, cmp::max(size, MIN_BUFFER_SIZE)),
+            // LONG, LONG RAW, which are described with size 0
+            8         => (OCIDataType::LongVarChar, long_size),
+            24        => (OCIDataType::LongVarRaw, long_size),
+            // NUMBER, FLOAT
+            2         => (OCIDataType::NumericWithLength, NUMBER_SIZE),
+            // BINARY_FLOAT, BINARY_DOUBLE
+            100 | 101 => (OCIDataType::Float, 8),
+            // DATE
+            12        => (OCIDataType::InternalDate, DATE_SIZE),
+            // TIMESTAMP, TIMESTAMP WITH TIME ZONE, TIMESTAMP WITH LOCAL TIME ZONE
+            187       => (OCIDataType::Timestamp, 0),
+            188       => (OCIDataType::TimestampWithTz, 0),
+            232       => (OCIDataType::TimestampWithLocalTz, 0),
+            // INTERVAL YEAR TO MONTH, INTERVAL DAY TO SECOND
+            182       => (OCIDataType::IntervalYearToMonth, 0),
+            183       => (OCIDataType::IntervalDayToSecond, 0),
+            // CLOB, BLOB
+            112       => (OCIDataType::Clob, 0),
+            113       => (OCIDataType::Blob, 0),
+            _         => (OCIDataType::Char, cmp::max(size * MAX_BYTES_PER_CHAR, MIN_BUFFER_SIZE)),
+        };
+        let descriptor = match descriptor_type(data_type) {
+            Some(dtype) => Some(Descriptor::new(env, dtype)?),
+            None        => None,
+        };
+        let define_type = data_type;
+        let data_type = match define_type {
+            OCIDataType::LongVarChar => OCIDataType::Char,
+            OCIDataType::LongVarRaw  => OCIDataType::Binary,
+            _                        => define_type,
+        };
+        Ok(Column {
+            data_type,
+            define_type,
+            buffer:      vec![0; size],
+            descriptor,
+            indicator:   0,
+            length:      0,
+            return_code: 0,
+        })
+    }
+
+    fn value(&self, stmt: &Statement) -> Result<SqlValue, OracleError> {
+        if self.indicator == -1 {
+            return Ok(SqlValue::null(self.data_type));
+        }
+        check_fetched(self.indicator, self.return_code, "Column::value")?;
+        match self.descriptor {
+            Some(ref descriptor) => read_descriptor(stmt, descriptor, self.data_type),
+            None if self.define_type != self.data_type => {
+                // The length of `SQLT_LVC` and `SQLT_LVB` values is in the buffer, `length` is too
+                // small for them.
+                let mut length = [0; LONG_LENGTH_SIZE];
+                length.copy_from_slice(&self.buffer[..LONG_LENGTH_SIZE]);
+                let end = cmp::min(LONG_LENGTH_SIZE + u32::from_ne_bytes(length) as usize, self.buffer.len());
+                buffer_value(stmt, self.data_type, &self.buffer[LONG_LENGTH_SIZE..end])
+            },
+            None => buffer_value(stmt, self.data_type, &self.buffer[..self.length as usize]),
+        }
+    }
+}
+
+/// Fails for a value fetched with an error in its column-level return code, e.g. `ORA-01406`
+/// when it was truncated to fit its buffer.
+fn check_fetched(indicator: c_short, return_code: c_ushort, location: &str) -> Result<(), OracleError> {
+    match (indicator, return_code) {
+        (0, 0)    => Ok(()),
+        (_, 0)    => Err(OracleError::with_status(1406,
+                                                  "ORA-01406: fetched column value was truncated".to_string(),
+                                                  location,
+                                                  OciStatus::Error)),
+        (_, code) => Err(OracleError::with_status(code as isize,
+                                                  format!("ORA-{:05}: fetching column value failed", code),
+                                                  location,
+                                                  OciStatus::Error)),
+    }
+}
+
+/// Value of `data_type` in the define or out-bind buffer `data`, text is converted from the
+/// client character set.
+fn buffer_value(stmt: &Statement, data_type: OCIDataType, data: &[u8]) -> Result<SqlValue, OracleError> {
+    let encoding = stmt.conn.environment().encoding();
+    if data_type == OCIDataType::Char && encoding != Encoding::Utf8 {
+        Ok(SqlValue::new(data_type, encoding.decode(data)?.into_bytes()))
+    } else {
+        Ok(SqlValue::new(data_type, data.to_vec()))
+    }
+}
+
+/// Iterator over the rows of an executed query.
+///
+/// Rows are fetched one at a time with [`oci_stmt_fetch2`](fn.oci_stmt_fetch2.html).
+pub struct Rows<'stmt> {
+    stmt:    &'stmt Statement<'stmt>,
+    columns: Vec<Column>,
+    info:    Vec<ColumnInfo>,
+    done:    bool,
+}
+
+impl<'stmt> Rows<'stmt> {
+    /// Metadata of every column of the rows.
+    pub fn columns(&self) -> &[ColumnInfo] {
+        &self.info
+    }
+}
+
+impl<'stmt> Iterator for Rows<'stmt> {
+    type Item = Result<Row, OracleError>;
+
+    fn next(&mut self) -> Option<Result<Row, OracleError>> {
+        if self.done {
+            return None;
+        }
+        let error = self.stmt.conn.error_handle();
+        let (result, warning) = error.call_with_warning(|error| {
+            oci_stmt_fetch2(self.stmt.handle, error, 1, OCIFetchOrientation::Next, 0)
+        });
+        match result {
+            Ok(()) => {
+                match warning {
+                    // ORA-01406: fetched column value was truncated,
+                    // ORA-24345: a truncation or null fetch error occurred
+                    Some(warning) if warning.code() == 1406 || warning.code() == 24345 => return Some(Err(warning)),
+                    Some(warning) => error.record_warning(warning),
+                    None          => {},
+                }
+                let values = self.columns.iter().map(|column| column.value(self.stmt));
+                Some(values.collect::<Result<_, _>>().map(|values| Row { values }))
+            },
+            Err(err) => {
+                self.done = true;
+                if err.kind() == OciStatus::NoData { None } else { Some(Err(err)) }
+            },
+        }
+    }
+}
+
+/// Single fetched row.
+#[derive(Clone, Debug)]
+pub struct Row {
+    values: Vec<SqlValue>,
+}
+
+impl Row {
+    /// Number of columns in the row.
+    pub fn column_count(&self) -> usize {
+        self.values.len()
+    }
+
+    /// Value of the column at zero-based `index` converted to `T`.
+    pub fn get<T: FromSql>(&self, index: usize) -> Result<T, OracleError> {
+        match self.values.get(index) {
+            Some(value) => T::from_sql(value),
+            None        => Err(OracleError::new(&format!("Column index {} is out of range", index),
+                                                "Row::get")),
+        }
+    }
+
+    /// `BLOB` or `CLOB` of the column at zero-based `index`, opened on `conn`.
+    pub fn lob<'conn>(&self, conn: &'conn Connection, index: usize) -> Result<Lob<'conn>, OracleError> {
+        match self.values.get(index) {
+            Some(value) => Lob::new(conn, value),
+            None        => Err(OracleError::new(&format!("Column index {} is out of range", index),
+                                                "Row::lob")),
+        }
+    }
+
+    /// Value of the column at zero-based `index`.
+    pub fn value(&self, index: usize) -> Option<&SqlValue> {
+        self.values.get(index)
+    }
+
+    /// Values of all columns.
+    pub fn values(&self) -> &[SqlValue] {
+        &self.values
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::StatementType;
+
+    #[test]
+    fn maps_statement_types() {
+        let types = [(1, StatementType::Select), (2, StatementType::Update), (3, StatementType::Delete),
+                     (4, StatementType::Insert), (5, StatementType::Create), (6, StatementType::Drop),
+                     (7, StatementType::Alter), (8, StatementType::Begin), (9, StatementType::Declare),
+                     (10, StatementType::Call), (16, StatementType::Merge)];
+        for &(code, stmt_type) in &types {
+            assert_eq!(StatementType::from_code(code), stmt_type);
+        }
+        assert_eq!(StatementType::from_code(0), StatementType::Other(0));
+        assert_eq!(StatementType::from_code(11), StatementType::Other(11));
+        assert_eq!(StatementType::from_code(u16::MAX), StatementType::Other(u16::MAX));
+    }
+
+    #[test]
+    fn classifies_statement_types() {
+        let classes = |code| {
+            let stmt_type = StatementType::from_code(code);
+            (stmt_type.is_query(), stmt_type.is_dml(), stmt_type.is_ddl(), stmt_type.is_plsql())
+        };
+        assert_eq!(classes(1), (true, false, false, false));
+        for &code in &[2, 3, 4, 16] {
+            assert_eq!(classes(code), (false, true, false, false), "{}", code);
+        }
+        for &code in &[5, 6, 7] {
+            assert_eq!(classes(code), (false, false, true, false), "{}", code);
+        }
+        for &code in &[8, 9, 10] {
+            assert_eq!(classes(code), (false, false, false, true), "{}", code);
+        }
+        assert_eq!(classes(42), (false, false, false, false));
+    }
+}
@@ -0,0 +1,114 @@
+//! Statement cache of a session
+
+use std::cell::Cell;
+use {c_void, c_uint, OCIHandleType, OCIAttribute, OCIStmtPrepare2Mode, OracleError};
+use {oci_attr_set, oci_handle_attr_get};
+use connection::{self, Connection};
+use statement::{self, Statement};
+
+/// Hits and misses of the statement cache of a connection.
+#[derive(Default)]
+pub struct CacheCounters {
+    hits:   Cell<u64>,
+    misses: Cell<u64>,
+}
+
+/// Statement cache of a connection, returned by
+/// [`Connection::statement_cache`](struct.Connection.html#method.statement_cache).
+///
+/// Caching is off until [`set_size`](#method.set_size) is called. Statements prepared with a key
+/// are then put back into the cache under that key when dropped and taken from it by the next
+/// prepare with the same key, without parsing the text again. Values bound before stay bound.
+pub struct StatementCache<'conn> {
+    conn: &'conn Connection,
+}
+
+impl<'conn> StatementCache<'conn> {
+    /// Statement cache of `conn`.
+    pub fn new(conn: &'conn Connection) -> StatementCache<'conn> {
+        StatementCache { conn }
+    }
+
+    /// Maximum number of cached statements, 0 while caching is off.
+    pub fn size(&self) -> Result<u32, OracleError> {
+        let (size, _) = oci_handle_attr_get::<c_uint>(self.conn.service_context().as_ptr() as *mut c_void,
+                                                      OCIHandleType::Service,
+                                                      OCIAttribute::StmtCacheSize,
+                                                      self.conn.error_handle().as_ptr())?;
+        Ok(size)
+    }
+
+    /// Sets the maximum number of cached statements, which turns caching on, 0 turns it off and
+    /// empties the cache.
+    pub fn set_size(&self, size: u32) -> Result<(), OracleError> {
+        let mut size = size as c_uint;
+        oci_attr_set(self.conn.service_context().as_ptr() as *mut c_void,
+                     OCIHandleType::Service,
+                     &mut size as *mut c_uint as *mut c_void,
+                     OCIAttribute::StmtCacheSize,
+                     self.conn.error_handle().as_ptr())
+    }
+
+    /// Takes the statement cached under `key`, or prepares `sql` if there is none.
+    ///
+    /// The statement is cached under `key` when dropped.
+    pub fn prepare(&self, key: &str, sql: &str) -> Result<Statement<'conn>, OracleError> {
+        match self.find(key)? {
+            Some(stmt) => Ok(stmt),
+            None       => statement::prepare(self.conn, sql, key, OCIStmtPrepare2Mode::Default),
+        }
+    }
+
+    /// Takes the statement cached under `key` with `OCI_PREP2_CACHE_SEARCHONLY`, `None` if it is
+    /// not cached.
+    ///
+    /// Other errors are returned and not counted as a miss.
+    pub fn find(&self, key: &str) -> Result<Option<Statement<'conn>>, OracleError> {
+        let counters = connection::cache_counters(self.conn);
+        match search(self.conn, key)? {
+            Some(stmt) => {
+                counters.hits.set(counters.hits.get() + 1);
+                Ok(Some(stmt))
+            },
+            None => {
+                counters.misses.set(counters.misses.get() + 1);
+                Ok(None)
+            },
+        }
+    }
+
+    /// Removes the statement cached under `key` with `OCI_STRLS_CACHE_DELETE`.
+    ///
+    /// Returns whether it was cached. The lookup is not counted as a hit or miss.
+    pub fn remove(&self, key: &str) -> Result<bool, OracleError> {
+        match search(self.conn, key)? {
+            Some(mut stmt) => {
+                statement::delete_from_cache(&mut stmt);
+                Ok(true)
+            },
+            None => Ok(false),
+        }
+    }
+
+    /// Number of statements found in the cache by [`prepare`](#method.prepare) and
+    /// [`find`](#method.find).
+    pub fn hits(&self) -> u64 {
+        connection::cache_counters(self.conn).hits.get()
+    }
+
+    /// Number of statements not found in the cache by [`prepare`](#method.prepare) and
+    /// [`find`](#method.find).
+    pub fn misses(&self) -> u64 {
+        connection::cache_counters(self.conn).misses.get()
+    }
+}
+
+/// Takes the statement cached under `key`, `None` if it is not cached.
+fn search<'conn>(conn: &'conn Connection, key: &str) -> Result<Option<Statement<'conn>>, OracleError> {
+    match statement::prepare(conn, "", key, OCIStmtPrepare2Mode::CacheSearchOnly) {
+        Ok(stmt) => Ok(Some(stmt)),
+        // ORA-24431: Statement does not exist in the cache
+        Err(ref err) if err.code() == 24431 => Ok(None),
+        Err(err) => Err(err),
+    }
+}
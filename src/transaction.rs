@@ -0,0 +1,75 @@
+//! Transaction guard
+
+use OracleError;
+use connection::Connection;
+
+/// Transaction started with [`Connection::transaction`](struct.Connection.html#method.transaction).
+///
+/// Rolled back on drop unless [`commit`](#method.commit) was called. Autocommit of the connection
+/// is turned off while the transaction is open and restored when it ends.
+pub struct Transaction<'conn> {
+    conn:       &'conn Connection,
+    autocommit: bool,
+    done:       bool,
+}
+
+impl<'conn> Transaction<'conn> {
+    /// Starts a transaction on `conn`.
+    pub fn new(conn: &'conn Connection) -> Transaction<'conn> {
+        let autocommit = conn.autocommit();
+        conn.set_autocommit(false);
+        Transaction { conn, autocommit, done: false }
+    }
+
+    /// Connection the transaction runs on.
+    pub fn connection(&self) -> &'conn Connection {
+        self.conn
+    }
+
+    /// Commits the transaction.
+    pub fn commit(mut self) -> Result<(), OracleError> {
+        self.finish(true)
+    }
+
+    /// Rolls back the transaction.
+    pub fn rollback(mut self) -> Result<(), OracleError> {
+        self.finish(false)
+    }
+
+    /// Creates the savepoint `name` with `SAVEPOINT`.
+    pub fn savepoint(&self, name: &str) -> Result<(), OracleError> {
+        self.execute("SAVEPOINT", name)
+    }
+
+    /// Rolls back the work done after the savepoint `name` with `ROLLBACK TO SAVEPOINT`,
+    /// the transaction stays open.
+    pub fn rollback_to(&self, name: &str) -> Result<(), OracleError> {
+        self.execute("ROLLBACK TO SAVEPOINT", name)
+    }
+
+    fn execute(&self, command: &str, name: &str) -> Result<(), OracleError> {
+        // The name is part of the statement text, so only plain identifiers are accepted.
+        let valid = name.chars().next().is_some_and(|c| c.is_ascii_alphabetic())
+                    && name.len() <= 128
+                    && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$' || c == '#');
+        if !valid {
+            return Err(OracleError::new(&format!("Invalid savepoint name {:?}", name),
+                                        "Transaction::execute"));
+        }
+        self.conn.prepare(&format!("{} {}", command, name))?.execute().map(|_| ())
+    }
+
+    fn finish(&mut self, commit: bool) -> Result<(), OracleError> {
+        self.done = true;
+        self.conn.set_autocommit(self.autocommit);
+        if commit { self.conn.commit() } else { self.conn.rollback() }
+    }
+}
+
+impl<'conn> Drop for Transaction<'conn> {
+    fn drop(&mut self) {
+        if !self.done {
+            let _ = self.finish(false);
+        }
+    }
+}
@@ -0,0 +1,268 @@
+//! Values exchanged with Oracle
+
+use std::convert::TryFrom;
+use std::str;
+use std::sync::Arc;
+use {OCIDataType, OracleError};
+use handle::Descriptor;
+use number::OracleNumber;
+
+/// Value in one of the Oracle external data types.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SqlValue {
+    data_type: OCIDataType,
+    data:      Vec<u8>,
+    null:      bool,
+    // LOB locator, shared by the copies of a fetched `BLOB` or `CLOB`.
+    locator:   Option<Arc<Descriptor>>,
+}
+
+impl SqlValue {
+    /// Creates a value of `data_type` from its external representation.
+    pub fn new(data_type: OCIDataType, data: Vec<u8>) -> SqlValue {
+        SqlValue { data_type, data, null: false, locator: None }
+    }
+
+    /// Creates a `NULL` value of `data_type`.
+    pub fn null(data_type: OCIDataType) -> SqlValue {
+        SqlValue { data_type, data: Vec::new(), null: true, locator: None }
+    }
+
+    /// External data type of the value.
+    pub fn data_type(&self) -> OCIDataType {
+        self.data_type
+    }
+
+    /// External representation of the value, empty for `NULL`.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.data
+    }
+
+    /// Whether the value is `NULL`.
+    pub fn is_null(&self) -> bool {
+        self.null
+    }
+}
+
+/// Creates a `BLOB` or `CLOB` value referring to `locator`.
+pub fn from_locator(data_type: OCIDataType, locator: Arc<Descriptor>) -> SqlValue {
+    SqlValue { data_type, data: Vec::new(), null: false, locator: Some(locator) }
+}
+
+/// LOB locator of `value`, if it has one.
+pub fn locator(value: &SqlValue) -> Option<&Arc<Descriptor>> {
+    value.locator.as_ref()
+}
+
+/// Conversion of a Rust value into the external representation used for binds.
+pub trait ToSql {
+    /// Converts the value into a [`SqlValue`](struct.SqlValue.html).
+    fn to_sql(&self) -> Result<SqlValue, OracleError>;
+}
+
+impl ToSql for str {
+    fn to_sql(&self) -> Result<SqlValue, OracleError> {
+        Ok(SqlValue::new(OCIDataType::Char, self.as_bytes().to_vec()))
+    }
+}
+
+impl ToSql for String {
+    fn to_sql(&self) -> Result<SqlValue, OracleError> {
+        self.as_str().to_sql()
+    }
+}
+
+impl ToSql for i32 {
+    fn to_sql(&self) -> Result<SqlValue, OracleError> {
+        Ok(SqlValue::new(OCIDataType::Int, self.to_ne_bytes().to_vec()))
+    }
+}
+
+impl ToSql for i64 {
+    fn to_sql(&self) -> Result<SqlValue, OracleError> {
+        Ok(SqlValue::new(OCIDataType::Int, self.to_ne_bytes().to_vec()))
+    }
+}
+
+impl ToSql for u64 {
+    fn to_sql(&self) -> Result<SqlValue, OracleError> {
+        Ok(SqlValue::new(OCIDataType::Uint, self.to_ne_bytes().to_vec()))
+    }
+}
+
+impl ToSql for f64 {
+    fn to_sql(&self) -> Result<SqlValue, OracleError> {
+        Ok(SqlValue::new(OCIDataType::Float, self.to_ne_bytes().to_vec()))
+    }
+}
+
+/// Booleans are bound as `1` and `0`.
+impl ToSql for bool {
+    fn to_sql(&self) -> Result<SqlValue, OracleError> {
+        (*self as i32).to_sql()
+    }
+}
+
+impl ToSql for [u8] {
+    fn to_sql(&self) -> Result<SqlValue, OracleError> {
+        Ok(SqlValue::new(OCIDataType::Binary, self.to_vec()))
+    }
+}
+
+impl ToSql for Vec<u8> {
+    fn to_sql(&self) -> Result<SqlValue, OracleError> {
+        self.as_slice().to_sql()
+    }
+}
+
+impl<T: ToSql + ?Sized> ToSql for &T {
+    fn to_sql(&self) -> Result<SqlValue, OracleError> {
+        (**self).to_sql()
+    }
+}
+
+/// `None` is bound as `NULL`.
+impl<T: ToSql> ToSql for Option<T> {
+    fn to_sql(&self) -> Result<SqlValue, OracleError> {
+        match *self {
+            Some(ref value) => value.to_sql(),
+            None            => Ok(SqlValue::null(OCIDataType::Char)),
+        }
+    }
+}
+
+/// Conversion of a fetched value into a Rust value.
+pub trait FromSql: Sized {
+    /// Converts `value`. `NULL` is an error unless the target is an `Option`.
+    fn from_sql(value: &SqlValue) -> Result<Self, OracleError>;
+}
+
+impl FromSql for String {
+    fn from_sql(value: &SqlValue) -> Result<String, OracleError> {
+        check_not_null(value, "String")?;
+        match value.data_type() {
+            OCIDataType::Char   => text(value, "String").map(|text| text.to_string()),
+            OCIDataType::Int | OCIDataType::Uint => integer(value, "String").map(|n| n.to_string()),
+            OCIDataType::Float  => float(value, "String").map(|n| n.to_string()),
+            OCIDataType::NumericWithLength => OracleNumber::from_bytes(value.as_bytes()).map(|n| n.to_string()),
+            OCIDataType::Binary => Ok(value.as_bytes().iter().map(|b| format!("{:02X}", b)).collect()),
+            _                   => Err(conversion_error(value, "String")),
+        }
+    }
+}
+
+impl FromSql for i32 {
+    fn from_sql(value: &SqlValue) -> Result<i32, OracleError> {
+        integer(value, "i32").and_then(|n| i32::try_from(n).map_err(|_| overflow_error(n, "i32")))
+    }
+}
+
+impl FromSql for i64 {
+    fn from_sql(value: &SqlValue) -> Result<i64, OracleError> {
+        integer(value, "i64").and_then(|n| i64::try_from(n).map_err(|_| overflow_error(n, "i64")))
+    }
+}
+
+impl FromSql for i128 {
+    fn from_sql(value: &SqlValue) -> Result<i128, OracleError> {
+        integer(value, "i128")
+    }
+}
+
+impl FromSql for u64 {
+    fn from_sql(value: &SqlValue) -> Result<u64, OracleError> {
+        integer(value, "u64").and_then(|n| u64::try_from(n).map_err(|_| overflow_error(n, "u64")))
+    }
+}
+
+impl FromSql for f64 {
+    fn from_sql(value: &SqlValue) -> Result<f64, OracleError> {
+        float(value, "f64")
+    }
+}
+
+/// Any non-zero number is `true`.
+impl FromSql for bool {
+    fn from_sql(value: &SqlValue) -> Result<bool, OracleError> {
+        integer(value, "bool").map(|n| n != 0)
+    }
+}
+
+impl FromSql for Vec<u8> {
+    fn from_sql(value: &SqlValue) -> Result<Vec<u8>, OracleError> {
+        check_not_null(value, "Vec<u8>")?;
+        match value.data_type() {
+            OCIDataType::Binary | OCIDataType::Char => Ok(value.as_bytes().to_vec()),
+            _                                       => Err(conversion_error(value, "Vec<u8>")),
+        }
+    }
+}
+
+/// `NULL` is converted to `None`.
+impl<T: FromSql> FromSql for Option<T> {
+    fn from_sql(value: &SqlValue) -> Result<Option<T>, OracleError> {
+        if value.is_null() { Ok(None) } else { T::from_sql(value).map(Some) }
+    }
+}
+
+/// Fails for `NULL`, which only `Option<T>` accepts.
+pub fn check_not_null(value: &SqlValue, target: &str) -> Result<(), OracleError> {
+    if value.is_null() {
+        Err(OracleError::new(&format!("Cannot convert NULL to {}", target), "FromSql::from_sql"))
+    } else {
+        Ok(())
+    }
+}
+
+/// Error for a value of a data type `target` cannot be built from.
+pub fn conversion_error(value: &SqlValue, target: &str) -> OracleError {
+    OracleError::new(&format!("Cannot convert {:?} to {}", value.data_type(), target),
+                     "FromSql::from_sql")
+}
+
+fn overflow_error(number: i128, target: &str) -> OracleError {
+    OracleError::new(&format!("{} is out of range of {}", number, target), "FromSql::from_sql")
+}
+
+fn text<'a>(value: &'a SqlValue, target: &str) -> Result<&'a str, OracleError> {
+    str::from_utf8(value.as_bytes()).map_err(|_| conversion_error(value, target))
+}
+
+/// Reads a native integer of any width, an integral float, an `OCINumber` or a number in text form.
+fn integer(value: &SqlValue, target: &str) -> Result<i128, OracleError> {
+    check_not_null(value, target)?;
+    let bytes = value.as_bytes();
+    let number = match (value.data_type(), bytes.len()) {
+        (OCIDataType::Int, 1)  => Some(i8::from_ne_bytes([bytes[0]]) as i128),
+        (OCIDataType::Int, 2)  => Some(i16::from_ne_bytes([bytes[0], bytes[1]]) as i128),
+        (OCIDataType::Int, 4)  => <[u8; 4]>::try_from(bytes).ok().map(|b| i32::from_ne_bytes(b) as i128),
+        (OCIDataType::Int, 8)  => <[u8; 8]>::try_from(bytes).ok().map(|b| i64::from_ne_bytes(b) as i128),
+        (OCIDataType::Uint, 1) => Some(bytes[0] as i128),
+        (OCIDataType::Uint, 2) => Some(u16::from_ne_bytes([bytes[0], bytes[1]]) as i128),
+        (OCIDataType::Uint, 4) => <[u8; 4]>::try_from(bytes).ok().map(|b| u32::from_ne_bytes(b) as i128),
+        (OCIDataType::Uint, 8) => <[u8; 8]>::try_from(bytes).ok().map(|b| u64::from_ne_bytes(b) as i128),
+        (OCIDataType::Float, _) => {
+            let number = float(value, target)?;
+            if number.fract() == 0.0 && number.abs() < 1e38 { Some(number as i128) } else { None }
+        },
+        (OCIDataType::NumericWithLength, _) => OracleNumber::from_bytes(bytes)?.to_i128().ok(),
+        (OCIDataType::Char, _) => text(value, target)?.trim().parse().ok(),
+        _                      => None,
+    };
+    number.ok_or_else(|| conversion_error(value, target))
+}
+
+/// Reads a native float of any width, a native integer, an `OCINumber` or a number in text form.
+fn float(value: &SqlValue, target: &str) -> Result<f64, OracleError> {
+    check_not_null(value, target)?;
+    let bytes = value.as_bytes();
+    let number = match (value.data_type(), bytes.len()) {
+        (OCIDataType::Float, 4) => <[u8; 4]>::try_from(bytes).ok().map(|b| f32::from_ne_bytes(b) as f64),
+        (OCIDataType::Float, 8) => <[u8; 8]>::try_from(bytes).ok().map(f64::from_ne_bytes),
+        (OCIDataType::Int, _) | (OCIDataType::Uint, _) => Some(integer(value, target)? as f64),
+        (OCIDataType::NumericWithLength, _) => Some(OracleNumber::from_bytes(bytes)?.to_f64()),
+        (OCIDataType::Char, _)  => text(value, target)?.trim().parse().ok(),
+        _                       => None,
+    };
+    number.ok_or_else(|| conversion_error(value, target))
+}